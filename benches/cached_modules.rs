@@ -0,0 +1,117 @@
+//! Throughput of `/modules` served from the persistent cache tier, and the latency
+//! of cheap `/` requests made meanwhile on the same worker.
+//!
+//! The in-memory tier is disabled so every request reads and deserializes the
+//! cached module list, and requests run concurrently on a single-threaded actix
+//! runtime like one server worker. Run with `cargo bench --bench cached_modules`.
+
+use actix_web::{test, App};
+use api_ict::model::{Module, ModuleType, Text};
+use api_ict::{cache, route};
+use serde_json::json;
+use std::env;
+use std::fs;
+use std::rc::Rc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+const MODULES: usize = 2000;
+const CONCURRENCY: usize = 32;
+const REQUESTS: usize = 2000;
+const PROBES: usize = 50;
+const PROBE_INTERVAL: Duration = Duration::from_millis(10);
+
+fn main() {
+    let cache_dir = env::temp_dir().join(format!("api-ict-bench-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&cache_dir);
+    fs::create_dir_all(&cache_dir).unwrap();
+    env::set_var("CACHE_DIR", &cache_dir);
+    env::set_var("MEMORY_CACHE_SIZE", "0");
+
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+    // Built from the model, so schema changes break the build instead of the run.
+    let modules: Vec<Module> = (0..MODULES as i64)
+        .map(|number| Module {
+            number,
+            description: Text::One(
+                "Lorem ipsum dolor sit amet, consectetur adipiscing elit.".repeat(4),
+            ),
+            name: Text::One(format!("Module {}", number)),
+            year: number % 4 + 1,
+            version: 1,
+            last_modified: "2023-02-20T07:14:33Z".to_string(),
+            creation_date: "2021-02-26T07:44:10Z".to_string(),
+            r#type: Text::One("Berufsfachschule".to_string()),
+            module_type: Some(ModuleType {
+                id: "pflichtmodul".to_string(),
+                name: Text::One("Pflichtmodul".to_string()),
+            }),
+            mandatory: true,
+        })
+        .collect();
+    fs::write(
+        cache_dir.join(format!(
+            "{}.json",
+            cache::store_key("modules_de_default_default")
+        )),
+        json!({ "version": cache::CACHE_FORMAT_VERSION, "data": modules, "timestamp": now })
+            .to_string(),
+    )
+    .unwrap();
+
+    actix_web::rt::System::new().block_on(async {
+        let app = Rc::new(test::init_service(App::new().configure(route::config)).await);
+
+        let started = Instant::now();
+        let workers: Vec<_> = (0..CONCURRENCY)
+            .map(|_| {
+                let app = app.clone();
+                actix_web::rt::spawn(async move {
+                    for _ in 0..REQUESTS / CONCURRENCY {
+                        let req = test::TestRequest::get()
+                            .uri("/modules?lang=de")
+                            .to_request();
+                        let res = test::call_service(&*app, req).await;
+                        assert!(res.status().is_success());
+                        test::read_body(res).await;
+                    }
+                })
+            })
+            .collect();
+
+        let probe = {
+            let app = app.clone();
+            actix_web::rt::spawn(async move {
+                let mut total = Duration::ZERO;
+                for _ in 0..PROBES {
+                    // Includes the time waiting for the worker, not only handling.
+                    let due = Instant::now() + PROBE_INTERVAL;
+                    actix_web::rt::time::sleep_until(due.into()).await;
+                    let req = test::TestRequest::get().uri("/").to_request();
+                    test::read_body(test::call_service(&*app, req).await).await;
+                    total += due.elapsed();
+                }
+                total / PROBES as u32
+            })
+        };
+
+        for worker in workers {
+            worker.await.unwrap();
+        }
+        let elapsed = started.elapsed();
+        let probe_latency = probe.await.unwrap();
+
+        println!(
+            "cached /modules: {} requests with {} in flight in {:.2?}, {:.0} req/s",
+            REQUESTS,
+            CONCURRENCY,
+            elapsed,
+            REQUESTS as f64 / elapsed.as_secs_f64()
+        );
+        println!("/ meanwhile: {:.2?} mean latency", probe_latency);
+    });
+
+    fs::remove_dir_all(&cache_dir).unwrap();
+}
@@ -1,21 +1,112 @@
-use reqwest;
+use crate::config;
+use crate::error::ApiError;
+use crate::upstream::{self, UpstreamResponse};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
 use serde::Deserialize;
 use serde_json::Value;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tokio::sync::Mutex;
+
+const REFRESH_MARGIN: Duration = Duration::from_secs(60);
+const DEFAULT_TOKEN_LIFETIME: Duration = Duration::from_secs(300);
 
 #[derive(Debug, Deserialize)]
 struct TokenResponse {
     access_token: String,
+    expires_in: Option<u64>,
 }
 
-pub async fn get_token() -> Result<String, Box<dyn std::error::Error>> {
-    let body = reqwest::get("https://www.modulbaukasten.ch/assets/auth.php")
-        .await?
-        .text()
-        .await?;
+#[derive(Debug, Deserialize)]
+struct JwtClaims {
+    exp: u64,
+}
+
+struct CachedToken {
+    access_token: String,
+    expires_at: Instant,
+}
+
+// Holding the lock while fetching coalesces concurrent refreshes into one request.
+static TOKEN: Mutex<Option<CachedToken>> = Mutex::const_new(None);
+
+pub async fn get_token() -> Result<String, ApiError> {
+    let mut cached = TOKEN.lock().await;
+
+    if let Some(token) = cached.as_ref() {
+        if token.expires_at > Instant::now() + REFRESH_MARGIN {
+            return Ok(token.access_token.clone());
+        }
+    }
+
+    let token = fetch_token().await?;
+    let access_token = token.access_token.clone();
+    *cached = Some(token);
+
+    Ok(access_token)
+}
+
+/// Drops the cached token if it is still the one the upstream rejected.
+pub async fn invalidate_token(rejected: &str) {
+    let mut cached = TOKEN.lock().await;
 
-    if let Some(inner_string) = serde_json::from_str::<Value>(&body)?.as_str() {
-        return Ok(serde_json::from_str::<TokenResponse>(inner_string)?.access_token);
+    if cached
+        .as_ref()
+        .is_some_and(|token| token.access_token == rejected)
+    {
+        *cached = None;
     }
+}
+
+/// Sends `request` with a bearer token, refreshing the token and retrying once on a 401.
+pub async fn send_authorized(
+    request: reqwest::RequestBuilder,
+) -> Result<UpstreamResponse, ApiError> {
+    let retry = request.try_clone();
+    let token = get_token().await?;
+    let res = upstream::execute(request.bearer_auth(&token)).await?;
+
+    if res.status == 401 {
+        if let Some(retry) = retry {
+            invalidate_token(&token).await;
+            let token = get_token().await?;
+            return upstream::execute(retry.bearer_auth(token))
+                .await?
+                .error_for_status();
+        }
+    }
+
+    res.error_for_status()
+}
+
+async fn fetch_token() -> Result<CachedToken, ApiError> {
+    let body = upstream::execute(upstream::client().get(config::auth_url()))
+        .await?
+        .error_for_status()?
+        .body;
+
+    let token = serde_json::from_str::<Value>(&body)
+        .ok()
+        .and_then(|value| value.as_str().map(str::to_string))
+        .and_then(|inner_string| serde_json::from_str::<TokenResponse>(&inner_string).ok())
+        .ok_or_else(|| ApiError::UpstreamAuth("Failed to parse token response".to_string()))?;
+
+    let lifetime = jwt_lifetime(&token.access_token)
+        .or(token.expires_in.map(Duration::from_secs))
+        .unwrap_or(DEFAULT_TOKEN_LIFETIME);
+
+    Ok(CachedToken {
+        access_token: token.access_token,
+        expires_at: Instant::now() + lifetime,
+    })
+}
+
+fn jwt_lifetime(token: &str) -> Option<Duration> {
+    let payload = token.split('.').nth(1)?;
+    let claims = URL_SAFE_NO_PAD.decode(payload.trim_end_matches('=')).ok()?;
+    let claims: JwtClaims = serde_json::from_slice(&claims).ok()?;
+
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).ok()?.as_secs();
 
-    Err("Failed to parse token response".into())
+    Some(Duration::from_secs(claims.exp.saturating_sub(now)))
 }
@@ -0,0 +1,70 @@
+use crate::error::ApiError;
+use std::any::Any;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex, OnceLock};
+use tokio::sync::OnceCell;
+
+type Flight<T> = OnceCell<Result<T, ApiError>>;
+
+static FLIGHTS: OnceLock<Mutex<HashMap<String, Arc<dyn Any + Send + Sync>>>> = OnceLock::new();
+
+fn flights() -> &'static Mutex<HashMap<String, Arc<dyn Any + Send + Sync>>> {
+    FLIGHTS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+pub fn in_flight(key: &str) -> bool {
+    flights().lock().unwrap().contains_key(key)
+}
+
+/// Runs `work` at most once per `key` at a time; concurrent callers await and share
+/// its result instead of starting their own.
+pub async fn single_flight<T, F, Fut>(key: &str, work: F) -> Result<T, ApiError>
+where
+    T: Clone + Send + Sync + 'static,
+    F: FnOnce() -> Fut,
+    Fut: Future<Output = Result<T, ApiError>>,
+{
+    let flight = {
+        let mut flights = flights().lock().unwrap();
+        let flight = flights
+            .entry(key.to_string())
+            .or_insert_with(|| Arc::new(Flight::<T>::new()))
+            .clone();
+
+        match flight.downcast::<Flight<T>>() {
+            Ok(flight) => flight,
+            // Same key used with another type, run without sharing.
+            Err(_) => Arc::new(Flight::<T>::new()),
+        }
+    };
+
+    // Cleans up in a guard so a caller dropped mid-flight (client disconnect) does not
+    // leave the key marked as in flight.
+    let guard = FlightGuard { key, flight };
+    guard.flight.get_or_init(work).await.clone()
+}
+
+struct FlightGuard<'a, T: Send + Sync + 'static> {
+    key: &'a str,
+    flight: Arc<Flight<T>>,
+}
+
+impl<T: Send + Sync + 'static> Drop for FlightGuard<'_, T> {
+    fn drop(&mut self) {
+        let mut flights = flights().lock().unwrap();
+        let Some(current) = flights.get(self.key) else {
+            return;
+        };
+        let Ok(current) = current.clone().downcast::<Flight<T>>() else {
+            return;
+        };
+
+        // An unfinished flight stays while other callers wait on it, one of them takes over.
+        // The map, this guard and `current` hold the three expected references.
+        let abandoned = Arc::strong_count(&current) == 3;
+        if Arc::ptr_eq(&current, &self.flight) && (self.flight.initialized() || abandoned) {
+            flights.remove(self.key);
+        }
+    }
+}
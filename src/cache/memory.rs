@@ -0,0 +1,91 @@
+use std::any::Any;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+struct Slot {
+    value: Arc<dyn Any + Send + Sync>,
+    timestamp: u64,
+    tick: u64,
+}
+
+/// Bounded least-recently-used map holding decoded cache values.
+pub struct MemoryCache {
+    capacity: usize,
+    tick: u64,
+    slots: HashMap<String, Slot>,
+    recency: BTreeMap<u64, String>,
+}
+
+impl MemoryCache {
+    pub fn new(capacity: usize) -> Self {
+        MemoryCache {
+            capacity,
+            tick: 0,
+            slots: HashMap::new(),
+            recency: BTreeMap::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.slots.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.slots.is_empty()
+    }
+
+    /// Returns the value and its timestamp, marking the key as recently used. Only
+    /// the `Arc` is cloned, so the lock is never held while copying a large value.
+    pub fn get<T: Send + Sync + 'static>(&mut self, key: &str) -> Option<(Arc<T>, u64)> {
+        self.tick += 1;
+        let tick = self.tick;
+        let slot = self.slots.get_mut(key)?;
+        let value = slot.value.clone().downcast::<T>().ok()?;
+
+        self.recency.remove(&slot.tick);
+        self.recency.insert(tick, key.to_string());
+        slot.tick = tick;
+
+        Some((value, slot.timestamp))
+    }
+
+    pub fn insert<T: Send + Sync + 'static>(&mut self, key: &str, value: Arc<T>, timestamp: u64) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        self.remove(key);
+        while self.slots.len() >= self.capacity {
+            let Some((_, oldest)) = self.recency.pop_first() else {
+                break;
+            };
+            self.slots.remove(&oldest);
+        }
+
+        self.tick += 1;
+        self.recency.insert(self.tick, key.to_string());
+        self.slots.insert(
+            key.to_string(),
+            Slot {
+                value,
+                timestamp,
+                tick: self.tick,
+            },
+        );
+    }
+
+    pub fn remove(&mut self, key: &str) {
+        if let Some(slot) = self.slots.remove(key) {
+            self.recency.remove(&slot.tick);
+        }
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &String> {
+        self.slots.keys()
+    }
+
+    pub fn clear(&mut self) {
+        self.slots.clear();
+        self.recency.clear();
+    }
+}
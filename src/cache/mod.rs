@@ -0,0 +1,432 @@
+pub mod flight;
+pub mod memory;
+pub mod policy;
+pub mod store;
+
+use crate::config;
+use crate::error::ApiError;
+use memory::MemoryCache;
+pub use policy::{CachePolicy, Resource};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use store::CacheStore;
+use tokio::sync::Semaphore;
+
+static MEMORY: OnceLock<Mutex<MemoryCache>> = OnceLock::new();
+
+static MEMORY_HITS: AtomicU64 = AtomicU64::new(0);
+static MEMORY_MISSES: AtomicU64 = AtomicU64::new(0);
+static DISK_HITS: AtomicU64 = AtomicU64::new(0);
+static DISK_MISSES: AtomicU64 = AtomicU64::new(0);
+
+/// Changed by every purge, so replicas sharing the store drop their memory tier too.
+const PURGE_GENERATION_KEY: &str = "_purge_generation";
+/// How often the memory tier looks for purges made by other replicas.
+const PURGE_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+static PURGE_GENERATION: Mutex<Option<Vec<u8>>> = Mutex::new(None);
+static LAST_PURGE_CHECK: AtomicU64 = AtomicU64::new(0);
+
+/// Bump whenever a cached type changes shape, so entries written by older
+/// releases live under other store keys and are refetched instead of failing to load.
+pub const CACHE_FORMAT_VERSION: u32 = 5;
+
+/// The store key of `cache_key`, namespaced by format version so replicas of
+/// different releases sharing one store never read each other's entries.
+pub fn store_key(cache_key: &str) -> String {
+    format!("v{}_{}", CACHE_FORMAT_VERSION, cache_key)
+}
+
+/// The cache key behind a store key of any format version, unversioned ones included.
+fn cache_key_of(store_key: &str) -> &str {
+    store_key
+        .strip_prefix('v')
+        .and_then(|rest| rest.split_once('_'))
+        .filter(|(version, _)| !version.is_empty() && version.bytes().all(|b| b.is_ascii_digit()))
+        .map_or(store_key, |(_, cache_key)| cache_key)
+}
+
+#[derive(Serialize, Deserialize)]
+struct CacheEntry<T> {
+    /// Missing in entries written before the format was versioned.
+    #[serde(default)]
+    version: u32,
+    data: T,
+    timestamp: u64,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, PartialEq, Eq)]
+pub struct CacheStats {
+    pub memory_hits: u64,
+    pub memory_misses: u64,
+    pub disk_hits: u64,
+    pub disk_misses: u64,
+}
+
+pub fn stats() -> CacheStats {
+    CacheStats {
+        memory_hits: MEMORY_HITS.load(Ordering::Relaxed),
+        memory_misses: MEMORY_MISSES.load(Ordering::Relaxed),
+        disk_hits: DISK_HITS.load(Ordering::Relaxed),
+        disk_misses: DISK_MISSES.load(Ordering::Relaxed),
+    }
+}
+
+fn memory() -> &'static Mutex<MemoryCache> {
+    MEMORY.get_or_init(|| Mutex::new(MemoryCache::new(config::memory_cache_size())))
+}
+
+/// Empties the in-memory tier; entries are reloaded from disk on next access.
+pub fn clear_memory() {
+    memory().lock().unwrap().clear();
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct CacheKeyInfo {
+    pub key: String,
+    /// Seconds since the entry was stored.
+    pub age: u64,
+    /// Size of the entry on disk in bytes.
+    pub size: u64,
+}
+
+#[derive(Deserialize)]
+struct CacheEntryHeader {
+    timestamp: u64,
+}
+
+/// Lists the entries of the current format version in the backing store, sorted by key.
+pub async fn list_entries() -> Result<Vec<CacheKeyInfo>, ApiError> {
+    with_store(|backend| {
+        let namespace = store_key("");
+        let mut entries = Vec::new();
+        for stored in backend.keys()? {
+            let Some(key) = stored.strip_prefix(&namespace) else {
+                continue;
+            };
+            let Some(content) = backend.get(&stored)? else {
+                continue;
+            };
+            let timestamp = serde_json::from_slice::<CacheEntryHeader>(&content)
+                .map(|header| header.timestamp)
+                .unwrap_or(0);
+
+            entries.push(CacheKeyInfo {
+                key: key.to_string(),
+                age: age(timestamp),
+                size: content.len() as u64,
+            });
+        }
+
+        Ok(entries)
+    })
+    .await
+}
+
+/// Removes every entry whose key starts with `prefix` from both tiers, in every
+/// format version; an empty prefix purges everything. Returns the number of
+/// entries removed from the store.
+pub async fn purge_prefix(prefix: &str) -> Result<usize, ApiError> {
+    {
+        let mut memory = memory().lock().unwrap();
+        let keys: Vec<String> = memory
+            .keys()
+            .filter(|key| key.starts_with(prefix))
+            .cloned()
+            .collect();
+        for key in keys {
+            memory.remove(&key);
+        }
+    }
+
+    let prefix = prefix.to_string();
+    with_store(move |backend| {
+        let mut purged = 0;
+        for key in backend.keys()? {
+            if key != PURGE_GENERATION_KEY
+                && cache_key_of(&key).starts_with(&prefix)
+                && backend.remove(&key)?
+            {
+                purged += 1;
+            }
+        }
+        announce_purge(backend)?;
+
+        Ok(purged)
+    })
+    .await
+}
+
+/// Removes a single entry from both tiers and every format version, returning
+/// whether it was in the store.
+pub async fn purge_key(cache_key: &str) -> Result<bool, ApiError> {
+    memory().lock().unwrap().remove(cache_key);
+
+    let cache_key = cache_key.to_string();
+    with_store(move |backend| {
+        let mut removed = false;
+        for key in backend.keys()? {
+            if key != PURGE_GENERATION_KEY && cache_key_of(&key) == cache_key {
+                removed |= backend.remove(&key)?;
+            }
+        }
+        announce_purge(backend)?;
+
+        Ok(removed)
+    })
+    .await
+}
+
+/// Stores a new purge generation. Other replicas see it within `PURGE_CHECK_INTERVAL`
+/// and clear their memory tier, which may still hold the purged entries.
+fn announce_purge(backend: &dyn CacheStore) -> Result<(), ApiError> {
+    let generation = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_nanos()
+        .to_string()
+        .into_bytes();
+    backend.put(PURGE_GENERATION_KEY, &generation)?;
+    *PURGE_GENERATION.lock().unwrap() = Some(generation);
+
+    Ok(())
+}
+
+/// Clears the memory tier when another replica purged the shared store, checking at
+/// most once per `PURGE_CHECK_INTERVAL`.
+async fn sync_purges() {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as u64;
+    let last = LAST_PURGE_CHECK.load(Ordering::Relaxed);
+    if now.saturating_sub(last) < PURGE_CHECK_INTERVAL.as_millis() as u64
+        || LAST_PURGE_CHECK
+            .compare_exchange(last, now, Ordering::Relaxed, Ordering::Relaxed)
+            .is_err()
+    {
+        return;
+    }
+
+    match with_store(|backend| backend.get(PURGE_GENERATION_KEY)).await {
+        Ok(generation) => {
+            let mut known = PURGE_GENERATION.lock().unwrap();
+            if *known != generation {
+                clear_memory();
+                *known = generation;
+            }
+        }
+        Err(err) => eprintln!("Error checking for cache purges: {}", err),
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
+
+fn age(timestamp: u64) -> u64 {
+    now().saturating_sub(timestamp)
+}
+
+/// Runs store I/O and (de)serialization on the blocking pool, so file reads,
+/// database locks and server round trips never stall the actix worker. At most one
+/// task per core runs at once, more would only compete with the workers for CPU time.
+async fn with_store<R, F>(work: F) -> Result<R, ApiError>
+where
+    R: Send + 'static,
+    F: FnOnce(&dyn CacheStore) -> Result<R, ApiError> + Send + 'static,
+{
+    static PERMITS: OnceLock<Semaphore> = OnceLock::new();
+
+    let permits = PERMITS.get_or_init(|| {
+        Semaphore::new(thread::available_parallelism().map_or(1, |cores| cores.get()))
+    });
+    let _permit = permits
+        .acquire()
+        .await
+        .map_err(|err| ApiError::Internal(format!("Cache task failed: {}", err)))?;
+    let backend = store::backend()?;
+    tokio::task::spawn_blocking(move || work(&*backend))
+        .await
+        .map_err(|err| ApiError::Internal(format!("Cache task failed: {}", err)))?
+}
+
+async fn lookup<T>(cache_key: &str) -> Result<Option<(Arc<T>, u64)>, ApiError>
+where
+    T: DeserializeOwned + Send + Sync + 'static,
+{
+    sync_purges().await;
+
+    let cached = memory().lock().unwrap().get::<T>(cache_key);
+    if let Some(cached) = cached {
+        MEMORY_HITS.fetch_add(1, Ordering::Relaxed);
+        return Ok(Some(cached));
+    }
+    MEMORY_MISSES.fetch_add(1, Ordering::Relaxed);
+
+    let key = store_key(cache_key);
+    let stored = with_store(move |backend| {
+        let Some(content) = backend.get(&key)? else {
+            return Ok(None);
+        };
+
+        // Parsing the whole entry measured faster than streaming it (see `benches/README.md`).
+        match serde_json::from_slice::<CacheEntry<T>>(&content) {
+            Ok(entry) if entry.version == CACHE_FORMAT_VERSION => Ok(Some(entry)),
+            // A readable entry of another format version is a plain miss.
+            Ok(_) => Ok(None),
+            Err(err) => {
+                // An unreadable entry is a miss, moved aside for inspection and refetched.
+                eprintln!("Quarantining cache entry {}: {}", key, err);
+                backend.quarantine(&key)?;
+                Ok(None)
+            }
+        }
+    })
+    .await
+    // A failing store (e.g. a Redis outage) must not fail requests the upstream can serve.
+    .unwrap_or_else(|err| {
+        eprintln!("Error reading cache entry {}: {}", cache_key, err);
+        None
+    });
+
+    if let Some(cache_entry) = stored {
+        DISK_HITS.fetch_add(1, Ordering::Relaxed);
+        let data = Arc::new(cache_entry.data);
+        memory()
+            .lock()
+            .unwrap()
+            .insert(cache_key, data.clone(), cache_entry.timestamp);
+        return Ok(Some((data, cache_entry.timestamp)));
+    }
+    DISK_MISSES.fetch_add(1, Ordering::Relaxed);
+
+    Ok(None)
+}
+
+async fn save<T>(cache_key: &str, data: T) -> Result<Arc<T>, ApiError>
+where
+    T: Serialize + Send + Sync + 'static,
+{
+    let key = store_key(cache_key);
+    let (cache_entry, written) = with_store(move |backend| {
+        let cache_entry = CacheEntry {
+            version: CACHE_FORMAT_VERSION,
+            data,
+            timestamp: now(),
+        };
+
+        let written = backend.put_with(&key, &mut |writer| {
+            Ok(serde_json::to_writer(writer, &cache_entry)?)
+        });
+
+        Ok((cache_entry, written))
+    })
+    .await?;
+
+    // The data is still served, and kept in memory, when the store cannot take it.
+    if let Err(err) = written {
+        eprintln!(
+            "Warning: could not store cache entry {}: {}",
+            cache_key, err
+        );
+    }
+
+    let data = Arc::new(cache_entry.data);
+    memory()
+        .lock()
+        .unwrap()
+        .insert(cache_key, data.clone(), cache_entry.timestamp);
+
+    Ok(data)
+}
+
+/// Refreshes `cache_key` in the background unless a refresh is already running.
+fn revalidate<T, F, Fut>(cache_key: &str, fetch_data: F)
+where
+    T: Serialize + Clone + Send + Sync + 'static,
+    F: FnOnce() -> Fut + Send + 'static,
+    Fut: Future<Output = Result<T, ApiError>> + Send + 'static,
+{
+    if flight::in_flight(cache_key) {
+        return;
+    }
+
+    let cache_key = cache_key.to_string();
+    tokio::spawn(async move {
+        let refreshed = flight::single_flight(&cache_key, || async {
+            save(&cache_key, fetch_data().await?).await
+        })
+        .await;
+
+        if let Err(err) = refreshed {
+            eprintln!("Error refreshing cache entry {}: {}", cache_key, err);
+        }
+    });
+}
+
+/// Serves `cache_key` from memory, then from disk, and only then calls `fetch_data`,
+/// applying the TTL, stale-while-revalidate and stale-if-error windows of `resource`.
+pub async fn get_cached_data<T, F, Fut>(
+    resource: Resource,
+    cache_key: &str,
+    fetch_data: F,
+) -> Result<T, ApiError>
+where
+    T: Serialize + DeserializeOwned + Clone + Send + Sync + 'static,
+    F: FnOnce() -> Fut + Send + 'static,
+    Fut: Future<Output = Result<T, ApiError>> + Send + 'static,
+{
+    if config::cache_disabled() {
+        return fetch_data().await;
+    }
+
+    let policy = resource.policy();
+    let cached = lookup::<T>(cache_key).await?;
+
+    if let Some((data, timestamp)) = &cached {
+        let age = age(*timestamp);
+
+        if age < policy.ttl.as_secs() {
+            return Ok(T::clone(data));
+        }
+        if age < (policy.ttl + policy.stale_while_revalidate).as_secs() {
+            revalidate(cache_key, fetch_data);
+            return Ok(T::clone(data));
+        }
+    }
+
+    let fresh = flight::single_flight(cache_key, || async {
+        // A flight that finished while we looked up the cache has already stored the data.
+        if let Some((data, timestamp)) = lookup::<T>(cache_key).await? {
+            if age(timestamp) < policy.ttl.as_secs() {
+                return Ok(data);
+            }
+        }
+
+        save(cache_key, fetch_data().await?).await
+    })
+    .await;
+
+    match fresh {
+        Ok(data) => Ok(Arc::unwrap_or_clone(data)),
+        Err(err) if err.is_upstream_failure() => match cached {
+            Some((data, timestamp))
+                if age(timestamp) < (policy.ttl + policy.stale_if_error).as_secs() =>
+            {
+                eprintln!("Serving stale cache entry {}: {}", cache_key, err);
+                Ok(Arc::unwrap_or_clone(data))
+            }
+            _ => Err(err),
+        },
+        Err(err) => Err(err),
+    }
+}
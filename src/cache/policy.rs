@@ -0,0 +1,49 @@
+use crate::config;
+use std::time::Duration;
+
+const DEFAULT_TTL: Duration = Duration::from_secs(20736000); // 8 months in seconds
+const DEFAULT_STALE_WHILE_REVALIDATE: Duration = Duration::from_secs(86400); // 1 day
+const DEFAULT_STALE_IF_ERROR: Duration = Duration::from_secs(2592000); // 30 days
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Resource {
+    ModuleList,
+    ModuleDetail,
+    Objectives,
+    Jobs,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CachePolicy {
+    /// Entries younger than this are served without contacting the upstream.
+    pub ttl: Duration,
+    /// Expired entries are served while a background task refreshes them for this long.
+    pub stale_while_revalidate: Duration,
+    /// Expired entries are served instead of an upstream error for this long.
+    pub stale_if_error: Duration,
+}
+
+impl Resource {
+    fn ttl_variable(&self) -> &'static str {
+        match self {
+            Resource::ModuleList => "CACHE_TTL_MODULES",
+            Resource::ModuleDetail => "CACHE_TTL_MODULE",
+            Resource::Objectives => "CACHE_TTL_OBJECTIVES",
+            Resource::Jobs => "CACHE_TTL_JOBS",
+        }
+    }
+
+    pub fn policy(&self) -> CachePolicy {
+        CachePolicy {
+            ttl: config::duration_secs(
+                self.ttl_variable(),
+                config::duration_secs("CACHE_TTL", DEFAULT_TTL),
+            ),
+            stale_while_revalidate: config::duration_secs(
+                "CACHE_STALE_WHILE_REVALIDATE",
+                DEFAULT_STALE_WHILE_REVALIDATE,
+            ),
+            stale_if_error: config::duration_secs("CACHE_STALE_IF_ERROR", DEFAULT_STALE_IF_ERROR),
+        }
+    }
+}
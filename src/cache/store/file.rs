@@ -0,0 +1,128 @@
+use super::CacheStore;
+use crate::error::ApiError;
+use std::fs::{self, File};
+use std::io::{BufWriter, ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// One JSON file per key in a directory, which survives restarts.
+pub struct FileStore {
+    dir: PathBuf,
+}
+
+impl FileStore {
+    pub fn new(dir: impl Into<PathBuf>) -> FileStore {
+        FileStore { dir: dir.into() }
+    }
+
+    fn path(&self, key: &str) -> PathBuf {
+        // Keys embed query parameters, never let them escape the cache directory.
+        let file_name: String = key
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || c == '_' || c == '-' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+
+        self.dir.join(format!("{}.json", file_name))
+    }
+}
+
+/// Writes to a temporary file first so readers never observe a partial entry.
+fn write_atomic(
+    path: &Path,
+    write: &mut dyn FnMut(&mut dyn Write) -> Result<(), ApiError>,
+) -> Result<(), ApiError> {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+
+    let tmp_path = path.with_extension(format!(
+        "tmp.{}.{}",
+        std::process::id(),
+        COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+
+    let written = File::create(&tmp_path)
+        .map_err(ApiError::from)
+        .and_then(|file| {
+            let mut writer = BufWriter::new(file);
+            write(&mut writer)?;
+            writer.flush()?;
+            Ok(())
+        })
+        .and_then(|()| Ok(fs::rename(&tmp_path, path)?));
+    if written.is_err() {
+        let _ = fs::remove_file(&tmp_path);
+    }
+
+    written
+}
+
+impl CacheStore for FileStore {
+    fn get(&self, key: &str) -> Result<Option<Vec<u8>>, ApiError> {
+        match fs::read(self.path(key)) {
+            Ok(content) => Ok(Some(content)),
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    fn put(&self, key: &str, value: &[u8]) -> Result<(), ApiError> {
+        self.put_with(key, &mut |writer| Ok(writer.write_all(value)?))
+    }
+
+    fn put_with(
+        &self,
+        key: &str,
+        write: &mut dyn FnMut(&mut dyn Write) -> Result<(), ApiError>,
+    ) -> Result<(), ApiError> {
+        fs::create_dir_all(&self.dir)?;
+        write_atomic(&self.path(key), write)
+    }
+
+    fn remove(&self, key: &str) -> Result<bool, ApiError> {
+        match fs::remove_file(self.path(key)) {
+            Ok(()) => Ok(true),
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(false),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Renames the file into a `quarantine` subdirectory, where it is no longer listed.
+    fn quarantine(&self, key: &str) -> Result<(), ApiError> {
+        let path = self.path(key);
+        let quarantine_dir = self.dir.join("quarantine");
+        fs::create_dir_all(&quarantine_dir)?;
+
+        match fs::rename(&path, quarantine_dir.join(path.file_name().unwrap())) {
+            Ok(()) => Ok(()),
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(()),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    fn keys(&self) -> Result<Vec<String>, ApiError> {
+        let dir = match fs::read_dir(&self.dir) {
+            Ok(dir) => dir,
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(err) => return Err(err.into()),
+        };
+
+        let mut keys = Vec::new();
+        for file in dir {
+            let path = file?.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some("json") {
+                continue;
+            }
+            if let Some(key) = path.file_stem().and_then(|stem| stem.to_str()) {
+                keys.push(key.to_string());
+            }
+        }
+
+        keys.sort();
+        Ok(keys)
+    }
+}
@@ -0,0 +1,38 @@
+use super::CacheStore;
+use crate::error::ApiError;
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+/// Keeps entries in process memory only, for tests and single-shot runs.
+#[derive(Default)]
+pub struct InMemoryStore {
+    entries: Mutex<BTreeMap<String, Vec<u8>>>,
+}
+
+impl InMemoryStore {
+    pub fn new() -> InMemoryStore {
+        InMemoryStore::default()
+    }
+}
+
+impl CacheStore for InMemoryStore {
+    fn get(&self, key: &str) -> Result<Option<Vec<u8>>, ApiError> {
+        Ok(self.entries.lock().unwrap().get(key).cloned())
+    }
+
+    fn put(&self, key: &str, value: &[u8]) -> Result<(), ApiError> {
+        self.entries
+            .lock()
+            .unwrap()
+            .insert(key.to_string(), value.to_vec());
+        Ok(())
+    }
+
+    fn remove(&self, key: &str) -> Result<bool, ApiError> {
+        Ok(self.entries.lock().unwrap().remove(key).is_some())
+    }
+
+    fn keys(&self) -> Result<Vec<String>, ApiError> {
+        Ok(self.entries.lock().unwrap().keys().cloned().collect())
+    }
+}
@@ -0,0 +1,75 @@
+//! Byte-oriented backends behind the in-memory cache tier, selected with `CACHE_BACKEND`.
+
+mod file;
+mod memory;
+mod redis;
+mod sqlite;
+
+pub use file::FileStore;
+pub use memory::InMemoryStore;
+pub use redis::RedisStore;
+pub use sqlite::SqliteStore;
+
+use crate::config::{self, CacheBackend};
+use crate::error::ApiError;
+use std::io::Write;
+use std::sync::{Arc, RwLock};
+
+pub trait CacheStore: Send + Sync {
+    fn get(&self, key: &str) -> Result<Option<Vec<u8>>, ApiError>;
+
+    fn put(&self, key: &str, value: &[u8]) -> Result<(), ApiError>;
+
+    /// Stores what `write` produces, streamed where the backend allows it.
+    fn put_with(
+        &self,
+        key: &str,
+        write: &mut dyn FnMut(&mut dyn Write) -> Result<(), ApiError>,
+    ) -> Result<(), ApiError> {
+        let mut content = Vec::new();
+        write(&mut content)?;
+        self.put(key, &content)
+    }
+
+    /// Removes `key`, returning whether it was present.
+    fn remove(&self, key: &str) -> Result<bool, ApiError>;
+
+    /// Every stored key, sorted.
+    fn keys(&self) -> Result<Vec<String>, ApiError>;
+
+    /// Moves an unreadable entry out of the way. Backends without a place to keep it
+    /// for inspection delete it, the caller has logged why it could not be read.
+    fn quarantine(&self, key: &str) -> Result<(), ApiError> {
+        self.remove(key)?;
+        Ok(())
+    }
+}
+
+static BACKEND: RwLock<Option<Arc<dyn CacheStore>>> = RwLock::new(None);
+
+/// The configured backend, created on first use.
+pub fn backend() -> Result<Arc<dyn CacheStore>, ApiError> {
+    if let Some(backend) = BACKEND.read().unwrap().as_ref() {
+        return Ok(backend.clone());
+    }
+
+    let mut slot = BACKEND.write().unwrap();
+    if let Some(backend) = slot.as_ref() {
+        return Ok(backend.clone());
+    }
+
+    let backend: Arc<dyn CacheStore> = match config::cache_backend() {
+        CacheBackend::Filesystem => Arc::new(FileStore::new(config::cache_dir())),
+        CacheBackend::Sqlite => Arc::new(SqliteStore::open(config::cache_sqlite_path())?),
+        CacheBackend::Redis => Arc::new(RedisStore::new(&config::cache_redis_url())?),
+        CacheBackend::Memory => Arc::new(InMemoryStore::new()),
+    };
+    *slot = Some(backend.clone());
+
+    Ok(backend)
+}
+
+/// Replaces the backend, e.g. with an `InMemoryStore` in tests.
+pub fn set_backend(backend: Arc<dyn CacheStore>) {
+    *BACKEND.write().unwrap() = Some(backend);
+}
@@ -0,0 +1,229 @@
+use super::CacheStore;
+use crate::error::ApiError;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Namespaces our keys so the server can be shared with other applications.
+const KEY_PREFIX: &str = "api-ict:";
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+enum Reply {
+    Status,
+    Integer(i64),
+    Bulk(Option<Vec<u8>>),
+    Array(Vec<Reply>),
+}
+
+/// Shares one cache between replicas through any server speaking the Redis protocol.
+pub struct RedisStore {
+    address: String,
+    username: Option<String>,
+    password: Option<String>,
+    database: Option<String>,
+    connection: Mutex<Option<BufReader<TcpStream>>>,
+}
+
+impl RedisStore {
+    /// Accepts `redis://[[user]:password@]host[:port][/db]`.
+    pub fn new(url: &str) -> Result<RedisStore, ApiError> {
+        let parsed = reqwest::Url::parse(url)
+            .ok()
+            .filter(|parsed| parsed.scheme() == "redis")
+            .ok_or_else(|| ApiError::CacheIo(format!("Invalid Redis URL '{}'", url)))?;
+        let host = parsed
+            .host_str()
+            .ok_or_else(|| ApiError::CacheIo(format!("Redis URL '{}' has no host", url)))?;
+
+        Ok(RedisStore {
+            address: format!("{}:{}", host, parsed.port().unwrap_or(6379)),
+            username: Some(parsed.username().to_string()).filter(|user| !user.is_empty()),
+            password: parsed.password().map(str::to_string),
+            database: Some(parsed.path().trim_start_matches('/').to_string())
+                .filter(|db| !db.is_empty()),
+            connection: Mutex::new(None),
+        })
+    }
+
+    fn connect(&self) -> io::Result<BufReader<TcpStream>> {
+        // Bounded, the connect runs while the connection mutex is held, so every other
+        // call to this store waits for it.
+        let address = self.address.to_socket_addrs()?.next().ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("{} did not resolve", self.address),
+            )
+        })?;
+        let stream = TcpStream::connect_timeout(&address, TIMEOUT)?;
+        stream.set_read_timeout(Some(TIMEOUT))?;
+        stream.set_write_timeout(Some(TIMEOUT))?;
+        let mut connection = BufReader::new(stream);
+
+        if let Some(password) = &self.password {
+            let mut auth = vec![b"AUTH".as_slice()];
+            if let Some(username) = &self.username {
+                auth.push(username.as_bytes());
+            }
+            auth.push(password.as_bytes());
+            send(&mut connection, &auth)?;
+        }
+        if let Some(database) = &self.database {
+            send(&mut connection, &[b"SELECT", database.as_bytes()])?;
+        }
+
+        Ok(connection)
+    }
+
+    /// Runs one command, reconnecting once if the connection was dropped.
+    fn command(&self, args: &[&[u8]]) -> Result<Reply, ApiError> {
+        let mut connection = self.connection.lock().unwrap();
+
+        for attempt in 0..2 {
+            if connection.is_none() {
+                *connection = Some(self.connect()?);
+            }
+
+            match send(connection.as_mut().unwrap(), args) {
+                Ok(reply) => return Ok(reply),
+                Err(err) if err.kind() == io::ErrorKind::Other => {
+                    return Err(ApiError::CacheIo(format!("Redis error: {}", err)))
+                }
+                Err(err) => {
+                    *connection = None;
+                    if attempt == 1 {
+                        return Err(err.into());
+                    }
+                }
+            }
+        }
+
+        unreachable!("the second attempt always returns")
+    }
+}
+
+fn send(connection: &mut BufReader<TcpStream>, args: &[&[u8]]) -> io::Result<Reply> {
+    let mut request = format!("*{}\r\n", args.len()).into_bytes();
+    for arg in args {
+        request.extend_from_slice(format!("${}\r\n", arg.len()).as_bytes());
+        request.extend_from_slice(arg);
+        request.extend_from_slice(b"\r\n");
+    }
+    connection.get_mut().write_all(&request)?;
+
+    read_reply(connection)
+}
+
+fn protocol_error(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.to_string())
+}
+
+fn read_reply(connection: &mut BufReader<TcpStream>) -> io::Result<Reply> {
+    let mut line = String::new();
+    if connection.read_line(&mut line)? == 0 {
+        return Err(io::ErrorKind::UnexpectedEof.into());
+    }
+    let line = line.trim_end_matches("\r\n");
+    let (kind, rest) = line.split_at(line.len().min(1));
+    let length = || {
+        rest.parse::<i64>()
+            .map_err(|_| protocol_error("Invalid length in Redis reply"))
+    };
+
+    match kind {
+        "+" => Ok(Reply::Status),
+        // Server errors use `Other` so the caller does not mistake them for a dropped connection.
+        "-" => Err(io::Error::other(rest.to_string())),
+        ":" => Ok(Reply::Integer(length()?)),
+        "$" => {
+            let Ok(length) = usize::try_from(length()?) else {
+                return Ok(Reply::Bulk(None));
+            };
+            let mut data = vec![0; length + 2];
+            connection.read_exact(&mut data)?;
+            data.truncate(length);
+            Ok(Reply::Bulk(Some(data)))
+        }
+        "*" => {
+            let count = usize::try_from(length()?).unwrap_or(0);
+            (0..count)
+                .map(|_| read_reply(connection))
+                .collect::<io::Result<Vec<_>>>()
+                .map(Reply::Array)
+        }
+        _ => Err(protocol_error("Unknown Redis reply type")),
+    }
+}
+
+fn unexpected() -> ApiError {
+    ApiError::CacheIo("Unexpected Redis reply".to_string())
+}
+
+impl CacheStore for RedisStore {
+    fn get(&self, key: &str) -> Result<Option<Vec<u8>>, ApiError> {
+        let key = format!("{}{}", KEY_PREFIX, key);
+        match self.command(&[b"GET", key.as_bytes()])? {
+            Reply::Bulk(value) => Ok(value),
+            _ => Err(unexpected()),
+        }
+    }
+
+    fn put(&self, key: &str, value: &[u8]) -> Result<(), ApiError> {
+        let key = format!("{}{}", KEY_PREFIX, key);
+        match self.command(&[b"SET", key.as_bytes(), value])? {
+            Reply::Status => Ok(()),
+            _ => Err(unexpected()),
+        }
+    }
+
+    fn remove(&self, key: &str) -> Result<bool, ApiError> {
+        let key = format!("{}{}", KEY_PREFIX, key);
+        match self.command(&[b"DEL", key.as_bytes()])? {
+            Reply::Integer(removed) => Ok(removed > 0),
+            _ => Err(unexpected()),
+        }
+    }
+
+    fn keys(&self) -> Result<Vec<String>, ApiError> {
+        let pattern = format!("{}*", KEY_PREFIX);
+        let mut cursor = "0".to_string();
+        let mut keys = Vec::new();
+
+        loop {
+            let reply = self.command(&[
+                b"SCAN",
+                cursor.as_bytes(),
+                b"MATCH",
+                pattern.as_bytes(),
+                b"COUNT",
+                b"1000",
+            ])?;
+            let Reply::Array(mut parts) = reply else {
+                return Err(unexpected());
+            };
+            let (Some(Reply::Array(batch)), Some(Reply::Bulk(Some(next)))) =
+                (parts.pop(), parts.pop())
+            else {
+                return Err(unexpected());
+            };
+
+            for key in batch {
+                if let Reply::Bulk(Some(key)) = key {
+                    let key = String::from_utf8_lossy(&key);
+                    if let Some(key) = key.strip_prefix(KEY_PREFIX) {
+                        keys.push(key.to_string());
+                    }
+                }
+            }
+
+            cursor = String::from_utf8_lossy(&next).into_owned();
+            if cursor == "0" {
+                break;
+            }
+        }
+
+        keys.sort();
+        keys.dedup();
+        Ok(keys)
+    }
+}
@@ -0,0 +1,69 @@
+use super::CacheStore;
+use crate::error::ApiError;
+use rusqlite::{params, Connection, OptionalExtension};
+use std::path::Path;
+use std::sync::Mutex;
+
+/// All entries in one embedded SQLite database file.
+pub struct SqliteStore {
+    connection: Mutex<Connection>,
+}
+
+impl SqliteStore {
+    pub fn open(path: impl AsRef<Path>) -> Result<SqliteStore, ApiError> {
+        if let Some(parent) = path.as_ref().parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let connection = Connection::open(path)?;
+        // WAL lets several replicas on one volume read while another writes.
+        connection.pragma_update(None, "journal_mode", "WAL")?;
+        connection.execute(
+            "CREATE TABLE IF NOT EXISTS cache (key TEXT PRIMARY KEY, value BLOB NOT NULL)",
+            [],
+        )?;
+
+        Ok(SqliteStore {
+            connection: Mutex::new(connection),
+        })
+    }
+}
+
+impl CacheStore for SqliteStore {
+    fn get(&self, key: &str) -> Result<Option<Vec<u8>>, ApiError> {
+        Ok(self
+            .connection
+            .lock()
+            .unwrap()
+            .query_row("SELECT value FROM cache WHERE key = ?1", [key], |row| {
+                row.get(0)
+            })
+            .optional()?)
+    }
+
+    fn put(&self, key: &str, value: &[u8]) -> Result<(), ApiError> {
+        self.connection.lock().unwrap().execute(
+            "INSERT OR REPLACE INTO cache (key, value) VALUES (?1, ?2)",
+            params![key, value],
+        )?;
+        Ok(())
+    }
+
+    fn remove(&self, key: &str) -> Result<bool, ApiError> {
+        let removed = self
+            .connection
+            .lock()
+            .unwrap()
+            .execute("DELETE FROM cache WHERE key = ?1", [key])?;
+        Ok(removed > 0)
+    }
+
+    fn keys(&self) -> Result<Vec<String>, ApiError> {
+        let connection = self.connection.lock().unwrap();
+        let mut statement = connection.prepare("SELECT key FROM cache ORDER BY key")?;
+        let keys = statement
+            .query_map([], |row| row.get(0))?
+            .collect::<Result<Vec<String>, _>>()?;
+        Ok(keys)
+    }
+}
@@ -0,0 +1,145 @@
+use std::env;
+use std::time::Duration;
+
+const DEFAULT_AUTH_URL: &str = "https://www.modulbaukasten.ch/assets/auth.php";
+const DEFAULT_DYNAMICS_BASE_URL: &str = "https://ictbb.crm17.dynamics.com/api/data/v9.1";
+const DEFAULT_PDF_BASE_URL: &str = "https://www.modulbaukasten.ch/Module/";
+
+fn var_or(name: &str, default: &str) -> String {
+    env::var(name)
+        .ok()
+        .filter(|value| !value.is_empty())
+        .unwrap_or_else(|| default.to_string())
+}
+
+/// Reads a number of seconds from `name`, falling back to `default`.
+pub fn duration_secs(name: &str, default: Duration) -> Duration {
+    env::var(name)
+        .ok()
+        .and_then(|secs| secs.parse().ok())
+        .map(Duration::from_secs)
+        .unwrap_or(default)
+}
+
+pub fn auth_url() -> String {
+    var_or("MODULBAUKASTEN_AUTH_URL", DEFAULT_AUTH_URL)
+}
+
+/// Base of the Dynamics Web API, without a trailing slash.
+pub fn dynamics_base_url() -> String {
+    var_or("DYNAMICS_BASE_URL", DEFAULT_DYNAMICS_BASE_URL)
+        .trim_end_matches('/')
+        .to_string()
+}
+
+/// Prefix for module PDF links, always ending with a slash.
+pub fn pdf_base_url() -> String {
+    let url = var_or("PDF_BASE_URL", DEFAULT_PDF_BASE_URL);
+
+    if url.ends_with('/') {
+        url
+    } else {
+        format!("{}/", url)
+    }
+}
+
+pub fn cache_dir() -> String {
+    var_or("CACHE_DIR", ".cache")
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CacheBackend {
+    Filesystem,
+    Sqlite,
+    Redis,
+    Memory,
+}
+
+/// `CACHE_BACKEND`: `filesystem` (default), `sqlite`, `redis` or `memory`.
+pub fn cache_backend() -> CacheBackend {
+    match env::var("CACHE_BACKEND")
+        .unwrap_or_default()
+        .to_lowercase()
+        .as_str()
+    {
+        "sqlite" => CacheBackend::Sqlite,
+        "redis" => CacheBackend::Redis,
+        "memory" => CacheBackend::Memory,
+        _ => CacheBackend::Filesystem,
+    }
+}
+
+/// Database file of the `sqlite` backend, inside `CACHE_DIR` by default.
+pub fn cache_sqlite_path() -> String {
+    env::var("CACHE_SQLITE_PATH")
+        .ok()
+        .filter(|path| !path.is_empty())
+        .unwrap_or_else(|| format!("{}/cache.sqlite", cache_dir()))
+}
+
+pub fn cache_redis_url() -> String {
+    var_or("CACHE_REDIS_URL", "redis://127.0.0.1:6379")
+}
+
+/// Number of entries kept in the in-memory cache tier, `0` disables it.
+pub fn memory_cache_size() -> usize {
+    env::var("MEMORY_CACHE_SIZE")
+        .ok()
+        .and_then(|size| size.parse().ok())
+        .unwrap_or(1000)
+}
+
+/// Bearer secret for the `/admin` routes; they are disabled while it is unset.
+pub fn admin_token() -> Option<String> {
+    env::var("ADMIN_TOKEN")
+        .ok()
+        .filter(|token| !token.is_empty())
+}
+
+/// `DISABLE_CACHE=true` sends every request to the upstream.
+pub fn cache_disabled() -> bool {
+    env::var("DISABLE_CACHE").is_ok_and(|value| value == "true")
+}
+
+/// `CACHE_WARMUP=true` starts the background cache warmer with the server, unless
+/// the cache is disabled.
+pub fn warmup_enabled() -> bool {
+    env::var("CACHE_WARMUP").is_ok_and(|value| value == "true") && !cache_disabled()
+}
+
+/// Pause between two warm-up runs, `0` only warms the cache at startup.
+pub fn warmup_interval() -> Duration {
+    duration_secs("CACHE_WARMUP_INTERVAL", Duration::from_secs(86400))
+}
+
+pub fn warmup_concurrency() -> usize {
+    env::var("CACHE_WARMUP_CONCURRENCY")
+        .ok()
+        .and_then(|concurrency| concurrency.parse().ok())
+        .filter(|concurrency| *concurrency > 0)
+        .unwrap_or(4)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UpstreamMode {
+    Live,
+    Record,
+    Replay,
+}
+
+/// `UPSTREAM_MODE`: `live` (default), `record` or `replay`.
+pub fn upstream_mode() -> UpstreamMode {
+    match env::var("UPSTREAM_MODE")
+        .unwrap_or_default()
+        .to_lowercase()
+        .as_str()
+    {
+        "record" => UpstreamMode::Record,
+        "replay" => UpstreamMode::Replay,
+        _ => UpstreamMode::Live,
+    }
+}
+
+pub fn upstream_record_dir() -> String {
+    var_or("UPSTREAM_RECORD_DIR", ".recordings")
+}
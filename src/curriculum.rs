@@ -0,0 +1,84 @@
+use crate::cache::{get_cached_data, Resource};
+use crate::error::ApiError;
+use crate::job;
+use crate::model::{
+    Curriculum, CurriculumModule, CurriculumPlace, CurriculumYear, Language, Languages,
+};
+use crate::module::{capitalize, is_mandatory, level_year, module_type_of, newest_per_module};
+use crate::upstream::{self, ODataQuery};
+use std::collections::BTreeMap;
+
+pub async fn get_curriculum(job_id: &str, lang: &Option<String>) -> Result<Curriculum, ApiError> {
+    let language = Language::resolve(lang)?;
+    let not_found = || ApiError::UnknownJob(format!("Job {} not found", job_id));
+    let api_id = job::get_api_id(job_id).await?.ok_or_else(not_found)?;
+    let job = job::get_jobs(&Some(language.as_str().to_string()))
+        .await?
+        .into_iter()
+        .find(|job| job.id == job_id)
+        .ok_or_else(not_found)?;
+
+    let cache_key = format!("curriculum_{}_{}", job.id, language.as_str());
+    get_cached_data(Resource::ModuleList, &cache_key, move || async move {
+        let query = ODataQuery::new()
+            .filter(format!(
+                "beembk_Abschluss/beembk_abschlussid eq {}",
+                upstream::string_literal(&api_id)
+            ))
+            .expand(&[
+                "beembk_Lernort",
+                "beembk_Modul",
+                "beembk_Modultyp",
+                "beembk_Level",
+            ]);
+        let mappings = upstream::modulmappings(&query).await?;
+
+        let mut years: BTreeMap<i64, BTreeMap<String, Vec<CurriculumModule>>> = BTreeMap::new();
+        for (number, mapping) in newest_per_module(&mappings) {
+            let Some(modul) = mapping.modul.as_ref() else {
+                continue;
+            };
+            let place = capitalize(
+                mapping
+                    .lernort
+                    .as_ref()
+                    .map(|lernort| lernort.name(language))
+                    .unwrap_or(""),
+            );
+
+            years
+                .entry(level_year(mapping))
+                .or_default()
+                .entry(place)
+                .or_default()
+                .push(CurriculumModule {
+                    number,
+                    name: modul.title(language).to_string(),
+                    version: modul.version.unwrap_or_default(),
+                    module_type: module_type_of(mapping, &Languages::single(language)),
+                    mandatory: is_mandatory(mapping),
+                });
+        }
+
+        let years = years
+            .into_iter()
+            .map(|(year, places)| CurriculumYear {
+                year,
+                learning_places: places
+                    .into_iter()
+                    .map(|(name, mut modules)| {
+                        modules.sort_by_key(|module| module.number);
+                        CurriculumPlace { name, modules }
+                    })
+                    .collect(),
+            })
+            .collect();
+
+        Ok(Curriculum {
+            job_id: job.id,
+            name: job.name.get(language).to_string(),
+            years,
+        })
+    })
+    .await
+}
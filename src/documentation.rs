@@ -1,6 +1,7 @@
+use crate::error::ApiError;
 use serde_json::{json, Value};
 
-pub async fn get_documentation() -> Result<Value, Box<dyn std::error::Error>> {
+pub async fn get_documentation() -> Result<Value, ApiError> {
     let documentation = json!({
         "description": "This API allows you to list all ICT modules.",
         "endpoints": [
@@ -12,12 +13,27 @@ pub async fn get_documentation() -> Result<Value, Box<dyn std::error::Error>> {
                 "url": "/jobs",
                 "method": "GET",
             },
+            {
+                "url": "/jobs/{jobId}",
+                "method": "GET",
+                "parameters": [
+                    "lang"
+                ]
+            },
+            {
+                "url": "/jobs/{jobId}/curriculum",
+                "method": "GET",
+                "parameters": [
+                    "lang"
+                ]
+            },
             {
                 "url": "/modules",
                 "method": "GET",
                 "parameters": [
                     "job_id",
                     "lang" ,
+                    "module_type",
                     "year"
                 ]
             },
@@ -25,8 +41,22 @@ pub async fn get_documentation() -> Result<Value, Box<dyn std::error::Error>> {
                 "url": "/modules/{moduleId}",
                 "method": "GET",
                 "parameters": [
-                    "lang",
-                    "year"
+                    "job_id",
+                    "lang"
+                ],
+            },
+            {
+                "url": "/modules/{moduleId}/objectives",
+                "method": "GET",
+                "parameters": [
+                    "lang"
+                ],
+            },
+            {
+                "url": "/modules/{moduleId}/objectives/{objectiveNumber}",
+                "method": "GET",
+                "parameters": [
+                    "lang"
                 ],
             }
         ]
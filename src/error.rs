@@ -0,0 +1,150 @@
+use actix_web::http::StatusCode;
+use actix_web::{HttpResponse, ResponseError};
+use serde_json::json;
+use std::fmt;
+
+#[derive(Debug, Clone)]
+pub enum ApiError {
+    NotFound(String),
+    InvalidLanguage(String),
+    InvalidYear(String),
+    InvalidModuleId(String),
+    InvalidObjectiveNumber(String),
+    UnknownJob(String),
+    Unauthorized(String),
+    UpstreamUnavailable(String),
+    UpstreamAuth(String),
+    CacheIo(String),
+    Internal(String),
+}
+
+impl ApiError {
+    /// Stable machine-readable code, safe for clients to match on.
+    pub fn code(&self) -> &'static str {
+        match self {
+            ApiError::NotFound(_) => "not_found",
+            ApiError::InvalidLanguage(_) => "invalid_language",
+            ApiError::InvalidYear(_) => "invalid_year",
+            ApiError::InvalidModuleId(_) => "invalid_module_id",
+            ApiError::InvalidObjectiveNumber(_) => "invalid_objective_number",
+            ApiError::UnknownJob(_) => "unknown_job",
+            ApiError::Unauthorized(_) => "unauthorized",
+            ApiError::UpstreamUnavailable(_) => "upstream_unavailable",
+            ApiError::UpstreamAuth(_) => "upstream_auth",
+            ApiError::CacheIo(_) => "cache_io",
+            ApiError::Internal(_) => "internal",
+        }
+    }
+
+    /// Whether the upstream failed, as opposed to the request being invalid.
+    pub fn is_upstream_failure(&self) -> bool {
+        matches!(
+            self,
+            ApiError::UpstreamUnavailable(_) | ApiError::UpstreamAuth(_)
+        )
+    }
+
+    fn title(&self) -> &'static str {
+        match self {
+            ApiError::NotFound(_) => "Resource not found",
+            ApiError::InvalidLanguage(_) => "Unsupported language",
+            ApiError::InvalidYear(_) => "Invalid year",
+            ApiError::InvalidModuleId(_) => "Invalid module id",
+            ApiError::InvalidObjectiveNumber(_) => "Invalid objective number",
+            ApiError::UnknownJob(_) => "Unknown job",
+            ApiError::Unauthorized(_) => "Unauthorized",
+            ApiError::UpstreamUnavailable(_) => "Upstream service unavailable",
+            ApiError::UpstreamAuth(_) => "Upstream authentication failed",
+            ApiError::CacheIo(_) => "Cache error",
+            ApiError::Internal(_) => "Internal server error",
+        }
+    }
+
+    fn detail(&self) -> &str {
+        match self {
+            ApiError::NotFound(detail)
+            | ApiError::InvalidLanguage(detail)
+            | ApiError::InvalidYear(detail)
+            | ApiError::InvalidModuleId(detail)
+            | ApiError::InvalidObjectiveNumber(detail)
+            | ApiError::UnknownJob(detail)
+            | ApiError::Unauthorized(detail)
+            | ApiError::UpstreamUnavailable(detail)
+            | ApiError::UpstreamAuth(detail)
+            | ApiError::CacheIo(detail)
+            | ApiError::Internal(detail) => detail,
+        }
+    }
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.title(), self.detail())
+    }
+}
+
+impl std::error::Error for ApiError {}
+
+impl ResponseError for ApiError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            ApiError::NotFound(_) | ApiError::UnknownJob(_) => StatusCode::NOT_FOUND,
+            ApiError::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+            ApiError::InvalidLanguage(_)
+            | ApiError::InvalidYear(_)
+            | ApiError::InvalidModuleId(_)
+            | ApiError::InvalidObjectiveNumber(_) => StatusCode::BAD_REQUEST,
+            ApiError::UpstreamUnavailable(_) => StatusCode::SERVICE_UNAVAILABLE,
+            ApiError::UpstreamAuth(_) => StatusCode::BAD_GATEWAY,
+            ApiError::CacheIo(_) | ApiError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        let status = self.status_code();
+
+        if status.is_server_error() {
+            eprintln!("Error handling request: {}", self);
+        }
+
+        // RFC 7807 problem details
+        HttpResponse::build(status)
+            .content_type("application/problem+json")
+            .json(json!({
+                "type": "about:blank",
+                "title": self.title(),
+                "status": status.as_u16(),
+                "detail": self.detail(),
+                "code": self.code(),
+            }))
+    }
+}
+
+impl From<reqwest::Error> for ApiError {
+    fn from(err: reqwest::Error) -> Self {
+        match err.status() {
+            Some(reqwest::StatusCode::UNAUTHORIZED) | Some(reqwest::StatusCode::FORBIDDEN) => {
+                ApiError::UpstreamAuth(err.to_string())
+            }
+            _ => ApiError::UpstreamUnavailable(err.to_string()),
+        }
+    }
+}
+
+impl From<std::io::Error> for ApiError {
+    fn from(err: std::io::Error) -> Self {
+        ApiError::CacheIo(err.to_string())
+    }
+}
+
+impl From<rusqlite::Error> for ApiError {
+    fn from(err: rusqlite::Error) -> Self {
+        ApiError::CacheIo(err.to_string())
+    }
+}
+
+impl From<serde_json::Error> for ApiError {
+    fn from(err: serde_json::Error) -> Self {
+        ApiError::Internal(err.to_string())
+    }
+}
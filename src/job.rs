@@ -1,12 +1,33 @@
-use serde_json::{from_str, json, Value};
-use std::env;
+use crate::cache::{get_cached_data, Resource};
+use crate::error::ApiError;
+use crate::model::{Job, JobDetail, JobLinks, JobModules, Language, Languages, Translations};
+use crate::module::get_modules;
+use crate::upstream::{self, ODataQuery};
+use serde::{Deserialize, Serialize};
+use serde_json::from_str;
+use std::collections::{BTreeMap, HashMap};
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct JobRecord {
+    id: String,
+    api_id: String,
+    name: HashMap<String, String>,
+    duration: Option<i64>,
+}
+
+impl JobRecord {
+    fn name(&self, lang: Language) -> String {
+        self.name.get(lang.as_str()).cloned().unwrap_or_default()
+    }
+}
+
+/// Offline fallback for when `beembk_abschlusses` cannot be fetched.
 const JOBS: &str = r#"
 [
     {
         "id": "69201",
         "api_id": "3b900e4d-1667-ed11-9562-000d3a83015d",
-        "duration": "4",
+        "duration": 4,
         "name": {
             "fr": "Développeuse/Développeur de business numérique CFC (dès 2023)",
             "de": "Entwickler/in digitales Business EFZ (ab 2023)",
@@ -16,7 +37,7 @@ const JOBS: &str = r#"
     {
         "id": "88601",
         "api_id": "4f50e7f3-6b82-eb11-a812-0022486f6f83",
-        "duration": "4",
+        "duration": 4,
         "name": {
             "fr": "Informaticien/ne CFC développement d'applications",
             "de": "Informatiker/in EFZ Applikationsentwicklung",
@@ -26,7 +47,7 @@ const JOBS: &str = r#"
     {
         "id": "88613",
         "api_id": "13d8d40b-6d82-eb11-a812-0022486f6f83",
-        "duration": "4",
+        "duration": 4,
         "name": {
             "fr": "Informaticien/ne CFC Développement d'applications (dès 2021)",
             "de": "Informatiker/in EFZ Applikationsentwicklung (ab 2021)",
@@ -36,7 +57,7 @@ const JOBS: &str = r#"
     {
         "id": "88612",
         "api_id": "1eac87d6-6d82-eb11-a812-0022486f6f83",
-        "duration": "4",
+        "duration": 4,
         "name": {
             "fr": "Informaticien/ne CFC exploitation et infrastructure (dès 2021)",
             "de": "Informatiker/in EFZ Plattformentwicklung (ab 2021)",
@@ -44,9 +65,9 @@ const JOBS: &str = r#"
         }
     },
     {
-        "id": "88614",
+        "id": "88602",
         "api_id": "03a95323-bf92-eb11-b1ac-000d3a831ef4",
-        "duration": "4",
+        "duration": 4,
         "name": {
             "fr": "Informaticien/ne CFC informatique d'entreprise",
             "de": "Informatiker/in EFZ Betriebsinformatik",
@@ -56,7 +77,7 @@ const JOBS: &str = r#"
     {
         "id": "88614",
         "api_id": "706fb04c-6e82-eb11-a812-0022486f6f83",
-        "duration": "4",
+        "duration": 4,
         "name": {
             "fr": "Informaticien/ne CFC Informatique d'entreprise (dès 2021)",
             "de": "Betriebsinformatiker/in EFZ (ab 2021)",
@@ -66,7 +87,7 @@ const JOBS: &str = r#"
     {
         "id": "88603",
         "api_id": "56567396-6e82-eb11-a812-0022486f6f83",
-        "duration": "4",
+        "duration": 4,
         "name": {
             "fr": "Informaticien/ne CFC Technique des systèmes",
             "de": "Informatiker/in EFZ Systemtechnik",
@@ -76,7 +97,7 @@ const JOBS: &str = r#"
     {
         "id": "88609",
         "api_id": "d1aa2e12-e592-eb11-b1ac-000d3a831ef4",
-        "duration": "4",
+        "duration": 4,
         "name": {
             "fr": "Informaticien/ne du bâtiment CFC automatisation des bâtiments (dès 2021)",
             "de": "Gebäudeinformatiker/in EFZ Gebäudeautomation (ab 2021)",
@@ -86,7 +107,7 @@ const JOBS: &str = r#"
     {
         "id": "88610",
         "api_id": "2459e01c-e592-eb11-b1ac-000d3a831ef4",
-        "duration": "4",
+        "duration": 4,
         "name": {
             "fr": "Informaticien/ne du bâtiment CFC communication et multimédia (dès 2021)",
             "de": "Gebäudeinformatiker/in EFZ Kommunikation und Multimedia (ab 2021)",
@@ -96,7 +117,7 @@ const JOBS: &str = r#"
     {
         "id": "88608",
         "api_id": "a17098f7-6f82-eb11-a812-0022486f6f83",
-        "duration": "4",
+        "duration": 4,
         "name": {
             "fr": "Informaticien/ne du bâtiment CFC planification (dès 2021)",
             "de": "Gebäudeinformatiker/in EFZ Planung (ab 2021)",
@@ -106,7 +127,7 @@ const JOBS: &str = r#"
     {
         "id": "88606",
         "api_id": "f2cb37d4-6e82-eb11-a812-0022486f6f83",
-        "duration": "4",
+        "duration": 4,
         "name": {
             "fr": "Médiamaticien/ne CFC (dès 2019)",
             "de": "Mediamatiker/in EFZ (ab 2019)",
@@ -116,7 +137,7 @@ const JOBS: &str = r#"
     {
         "id": "88605",
         "api_id": "f1e7a970-6f82-eb11-a812-0022486f6f83",
-        "duration": "4",
+        "duration": 4,
         "name": {
             "fr": "Opératrice en informatique/Opérateur en informatique CFC",
             "de": "ICT-Fachfrau/ICT-Fachmann EFZ",
@@ -126,36 +147,150 @@ const JOBS: &str = r#"
 ]    
 "#;
 
-pub async fn get_jobs(lang: &Option<String>) -> Result<Value, Box<dyn std::error::Error>> {
-    let default_language = env::var("DEFAULT_LANGUAGE").unwrap_or_else(|_| "de".to_string());
-    let language = lang.as_deref().unwrap_or(&default_language);
+async fn job_records() -> Result<Vec<JobRecord>, ApiError> {
+    let cached = get_cached_data(Resource::Jobs, "jobs", || async {
+        let query = ODataQuery::new().select(&[
+            "beembk_abschlussid",
+            "beembk_berufsnummer",
+            "beembk_abschlussname",
+            "beembk_abschlussname_fr",
+            "beembk_abschlussname_it",
+            "beembk_ausbildungsdauer",
+            "modifiedon",
+        ]);
 
-    let jobs: Vec<Value> = from_str(JOBS)?;
+        let mut abschlusses = upstream::abschlusses(&query).await?;
+        // Of Abschlüsse sharing a number the most recently modified wins, whatever
+        // order the upstream returns them in; timestamps are ISO 8601 in UTC.
+        abschlusses.sort_by(|a, b| {
+            a.number
+                .cmp(&b.number)
+                .then_with(|| b.modified_on.cmp(&a.modified_on))
+                .then_with(|| a.id.cmp(&b.id))
+        });
 
-    let jobs_translated: Vec<Value> = jobs
-        .iter()
-        .map(|job| {
-            json!({
-                "id": job["id"],
-                "name": job["name"].get(language).unwrap_or(&json!("")),
-            })
+        let mut jobs: Vec<JobRecord> = Vec::new();
+        for abschluss in abschlusses {
+            let (Some(id), Some(api_id)) = (abschluss.number.clone(), abschluss.id.clone()) else {
+                continue;
+            };
+            if jobs.last().is_some_and(|job| job.id == id) {
+                eprintln!("Ignoring Abschluss {} with duplicate number {}", api_id, id);
+                continue;
+            }
+
+            let name = Language::ALL
+                .iter()
+                .map(|lang| (lang.as_str().to_string(), abschluss.name(*lang).to_string()))
+                .collect();
+            jobs.push(JobRecord {
+                id,
+                api_id,
+                name,
+                duration: abschluss.duration,
+            });
+        }
+
+        Ok(jobs)
+    })
+    .await;
+
+    match cached {
+        Ok(jobs) => Ok(jobs),
+        Err(err) if err.is_upstream_failure() => {
+            eprintln!("Serving the built-in job list: {}", err);
+            Ok(from_str(JOBS)?)
+        }
+        Err(err) => Err(err),
+    }
+}
+
+pub async fn get_jobs(lang: &Option<String>) -> Result<Vec<Job>, ApiError> {
+    let languages = Languages::resolve(lang)?;
+
+    let jobs = job_records().await?;
+
+    let jobs_translated: Vec<Job> = jobs
+        .into_iter()
+        .map(|job| Job {
+            name: languages.text(|language| job.name(language)),
+            id: job.id,
         })
         .collect();
 
-    Ok(json!(jobs_translated))
+    Ok(jobs_translated)
+}
+
+/// Jobs in `languages`, keyed by the `beembk_abschlussid` of their Abschluss.
+pub async fn jobs_by_api_id(languages: &Languages) -> Result<HashMap<String, Job>, ApiError> {
+    Ok(job_records()
+        .await?
+        .into_iter()
+        .map(|job| {
+            let name = languages.text(|language| job.name(language));
+            (job.api_id, Job { id: job.id, name })
+        })
+        .collect())
 }
 
-pub async fn get_api_id(job_id: &str) -> Result<Option<String>, Box<dyn std::error::Error>> {
+pub async fn get_api_id(job_id: &str) -> Result<Option<String>, ApiError> {
     if job_id.is_empty() {
         return Ok(None);
     }
 
-    let jobs: Vec<Value> = from_str(JOBS)?;
+    Ok(Some(find_job(job_id).await?.api_id))
+}
+
+async fn find_job(job_id: &str) -> Result<JobRecord, ApiError> {
+    job_records()
+        .await?
+        .into_iter()
+        .find(|job| job.id == job_id)
+        .ok_or_else(|| ApiError::UnknownJob(format!("Job {} not found", job_id)))
+}
+
+pub async fn get_job(job_id: &str, lang: &Option<String>) -> Result<JobDetail, ApiError> {
+    let language = Language::resolve(lang)?;
+    let job = find_job(job_id).await?;
+
+    let lang = Some(language.as_str().to_string());
+    let modules = get_modules(&lang, &None, &Some(job.id.clone()), &None).await?;
+
+    let mut per_year = BTreeMap::new();
+    let mut per_learning_place = BTreeMap::new();
+    for module in &modules {
+        *per_year.entry(module.year).or_insert(0) += 1;
+        *per_learning_place
+            .entry(module.r#type.get(language).to_string())
+            .or_insert(0) += 1;
+    }
 
-    let job = jobs
-        .iter()
-        .find(|job| job["id"] == job_id)
-        .ok_or("Job not found")?;
+    let years: Vec<i64> = match job.duration {
+        Some(duration) => (1..=duration).collect(),
+        None => per_year.keys().copied().collect(),
+    };
+    let modules_link = format!("/modules?job_id={}", job.id);
 
-    Ok(job["api_id"].as_str().map(|s| s.to_string()))
+    Ok(JobDetail {
+        name: Translations {
+            de: job.name(Language::De),
+            fr: job.name(Language::Fr),
+            it: job.name(Language::It),
+        },
+        duration: job.duration,
+        modules: JobModules {
+            total: modules.len(),
+            per_year,
+            per_learning_place,
+        },
+        links: JobLinks {
+            self_link: format!("/jobs/{}", job.id),
+            modules_per_year: years
+                .into_iter()
+                .map(|year| (year, format!("{}&year={}", modules_link, year)))
+                .collect(),
+            modules: modules_link,
+        },
+        id: job.id,
+    })
 }
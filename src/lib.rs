@@ -1,5 +1,13 @@
 pub mod auth;
+pub mod cache;
+pub mod config;
+pub mod curriculum;
 pub mod documentation;
+pub mod error;
 pub mod job;
+pub mod model;
 pub mod module;
+pub mod recording;
 pub mod route;
+pub mod upstream;
+pub mod warmup;
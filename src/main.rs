@@ -1,5 +1,5 @@
 use actix_web::{App, HttpServer};
-use api_ict::route;
+use api_ict::{config, route, warmup};
 use std::env;
 
 #[actix_web::main]
@@ -13,5 +13,9 @@ async fn main() -> std::io::Result<()> {
 
     println!("Server is running at port: {}", port);
 
+    if config::warmup_enabled() {
+        actix_web::rt::spawn(warmup::run_schedule());
+    }
+
     server.run().await
 }
@@ -0,0 +1,271 @@
+use crate::error::ApiError;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::env;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Language {
+    De,
+    Fr,
+    It,
+}
+
+impl Language {
+    pub const ALL: [Language; 3] = [Language::De, Language::Fr, Language::It];
+
+    pub fn parse(lang: &str) -> Result<Language, ApiError> {
+        match lang.to_lowercase().as_str() {
+            "de" => Ok(Language::De),
+            "fr" => Ok(Language::Fr),
+            "it" => Ok(Language::It),
+            _ => Err(ApiError::InvalidLanguage(format!(
+                "Language '{}' is not supported, use one of de, fr, it",
+                lang
+            ))),
+        }
+    }
+
+    /// Resolves the `lang` query parameter, falling back to `DEFAULT_LANGUAGE`.
+    pub fn resolve(lang: &Option<String>) -> Result<Language, ApiError> {
+        match lang {
+            Some(lang) => Language::parse(lang),
+            None => {
+                Language::parse(&env::var("DEFAULT_LANGUAGE").unwrap_or_else(|_| "de".to_string()))
+            }
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Language::De => "de",
+            Language::Fr => "fr",
+            Language::It => "it",
+        }
+    }
+}
+
+/// The languages requested with `lang`: one language, a comma separated list or `all`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Languages(Vec<Language>);
+
+impl Languages {
+    pub fn single(language: Language) -> Languages {
+        Languages(vec![language])
+    }
+
+    /// Resolves the `lang` query parameter, falling back to `DEFAULT_LANGUAGE`.
+    pub fn resolve(lang: &Option<String>) -> Result<Languages, ApiError> {
+        let Some(lang) = lang else {
+            return Ok(Languages::single(Language::resolve(&None)?));
+        };
+        if lang.eq_ignore_ascii_case("all") {
+            return Ok(Languages(Language::ALL.to_vec()));
+        }
+
+        let requested = lang
+            .split(',')
+            .map(|lang| Language::parse(lang.trim()))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Languages(
+            Language::ALL
+                .into_iter()
+                .filter(|language| requested.contains(language))
+                .collect(),
+        ))
+    }
+
+    /// Identifies the selection in cache keys, e.g. `de` or `de-fr`.
+    pub fn key(&self) -> String {
+        self.0
+            .iter()
+            .map(Language::as_str)
+            .collect::<Vec<_>>()
+            .join("-")
+    }
+
+    /// Builds `text` in every selected language.
+    pub fn text<S: Into<String>>(&self, text: impl Fn(Language) -> S) -> Text {
+        match self.0.as_slice() {
+            [language] => Text::One(text(*language).into()),
+            languages => Text::Many(
+                languages
+                    .iter()
+                    .map(|language| (language.as_str().to_string(), text(*language).into()))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+/// A translatable text, a plain string for a single language and an object keyed
+/// by language when several were requested.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum Text {
+    One(String),
+    Many(BTreeMap<String, String>),
+}
+
+impl Text {
+    /// The text in `lang`, or the only text there is.
+    pub fn get(&self, lang: Language) -> &str {
+        match self {
+            Text::One(text) => text,
+            Text::Many(texts) => texts.get(lang.as_str()).map_or("", String::as_str),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Job {
+    pub id: String,
+    pub name: Text,
+}
+
+/// A text in every supported language.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct Translations {
+    pub de: String,
+    pub fr: String,
+    pub it: String,
+}
+
+impl Translations {
+    pub fn get(&self, lang: Language) -> &str {
+        match lang {
+            Language::De => &self.de,
+            Language::Fr => &self.fr,
+            Language::It => &self.it,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct JobDetail {
+    pub id: String,
+    pub name: Translations,
+    /// Length of the apprenticeship in years.
+    pub duration: Option<i64>,
+    pub modules: JobModules,
+    pub links: JobLinks,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct JobModules {
+    pub total: usize,
+    pub per_year: BTreeMap<i64, usize>,
+    /// Keyed by the learning place name in the requested language.
+    pub per_learning_place: BTreeMap<String, usize>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct JobLinks {
+    #[serde(rename = "self")]
+    pub self_link: String,
+    pub modules: String,
+    pub modules_per_year: BTreeMap<i64, String>,
+}
+
+/// The modules of a job grouped by year and learning place.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Curriculum {
+    pub job_id: String,
+    pub name: String,
+    pub years: Vec<CurriculumYear>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CurriculumYear {
+    pub year: i64,
+    pub learning_places: Vec<CurriculumPlace>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CurriculumPlace {
+    pub name: String,
+    pub modules: Vec<CurriculumModule>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CurriculumModule {
+    pub number: i64,
+    pub name: String,
+    pub version: i64,
+    pub module_type: Option<ModuleType>,
+    pub mandatory: bool,
+}
+
+/// The Modultyp of a module within a job, e.g. Pflichtmodul or Wahlpflichtmodul.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ModuleType {
+    /// Language independent, the German name in lower case such as `pflichtmodul`.
+    pub id: String,
+    pub name: Text,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct LearningPlace {
+    pub year: i64,
+    #[serde(rename = "type")]
+    pub r#type: Text,
+    pub module_type: Option<ModuleType>,
+    pub mandatory: bool,
+}
+
+/// Where and when a job teaches a module.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ModuleUsage {
+    pub job: Job,
+    #[serde(flatten)]
+    pub place: LearningPlace,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Module {
+    pub number: i64,
+    pub description: Text,
+    pub name: Text,
+    pub year: i64,
+    pub version: i64,
+    pub last_modified: String,
+    pub creation_date: String,
+    #[serde(rename = "type")]
+    pub r#type: Text,
+    pub module_type: Option<ModuleType>,
+    pub mandatory: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ModuleDetail {
+    pub number: i64,
+    pub description: Text,
+    pub name: Text,
+    pub year: i64,
+    #[serde(rename = "type")]
+    pub r#type: Text,
+    pub module_type: Option<ModuleType>,
+    pub mandatory: bool,
+    pub version: i64,
+    pub last_modified: String,
+    pub creation_date: String,
+    pub pdf: Text,
+    pub competence: Text,
+    pub objectives: Vec<Objective>,
+    pub usages: Vec<ModuleUsage>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Objective {
+    /// The `beembk_handlungszielnr`, e.g. `110.1`.
+    pub number: String,
+    pub name: Text,
+    pub details: Vec<Knowledge>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Knowledge {
+    /// The `beembk_hanoknr`, e.g. `110.1.2`.
+    pub number: String,
+    pub text: Text,
+}
@@ -1,427 +1,457 @@
-use crate::{auth, job};
+use crate::cache::{get_cached_data, Resource};
+use crate::config;
+use crate::error::ApiError;
+use crate::job;
+use crate::model::{
+    Knowledge, Languages, LearningPlace, Module, ModuleDetail, ModuleType, ModuleUsage, Objective,
+};
+use crate::upstream::{self, ModulMapping, ODataQuery};
 use regex::Regex;
-use reqwest;
-use serde::{Deserialize, Serialize};
-use serde_json::{json, Value};
-use std::env;
-use std::fs;
-use std::path::Path;
-use std::time::{Duration, SystemTime, UNIX_EPOCH};
-
-const CACHE_DURATION: Duration = Duration::from_secs(20736000); // 8 months in seconds
-
-#[derive(Debug, Deserialize)]
-struct ApiResponse {
-    value: Vec<Value>,
-}
-
-#[derive(Serialize, Deserialize)]
-struct CacheEntry {
-    data: Value,
-    timestamp: u64,
-}
-
-fn get_cache_path(cache_key: &str) -> String {
-    format!(".cache/{}.json", cache_key)
-}
-
-fn is_cache_valid(timestamp: u64) -> bool {
-    let now = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap()
-        .as_secs();
-    now - timestamp < CACHE_DURATION.as_secs()
-}
-
-async fn get_cached_data<F, Fut>(
-    cache_key: &str,
-    fetch_data: F,
-) -> Result<Value, Box<dyn std::error::Error>>
-where
-    F: FnOnce() -> Fut,
-    Fut: std::future::Future<Output = Result<Value, Box<dyn std::error::Error>>>,
-{
-    if env::var("DISABLE_CACHE").unwrap_or_else(|_| "false".to_string()) == "true" {
-        return fetch_data().await;
-    }
-
-    let cache_path = get_cache_path(cache_key);
-
-    if Path::new(&cache_path).exists() {
-        let cache_content = fs::read_to_string(&cache_path)?;
-        let cache_entry: CacheEntry = serde_json::from_str(&cache_content)?;
-
-        if is_cache_valid(cache_entry.timestamp) {
-            return Ok(cache_entry.data);
-        }
-    }
-
-    let fresh_data = fetch_data().await?;
-
-    fs::create_dir_all(".cache")?;
-
-    let cache_entry = CacheEntry {
-        data: fresh_data.clone(),
-        timestamp: SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_secs(),
-    };
-
-    fs::write(&cache_path, serde_json::to_string(&cache_entry)?)?;
-
-    Ok(fresh_data)
-}
+use std::collections::HashMap;
+use std::sync::OnceLock;
 
 pub async fn get_modules(
     lang: &Option<String>,
     year: &Option<String>,
     job_id: &Option<String>,
-) -> Result<Value, Box<dyn std::error::Error>> {
-    let default_language = env::var("DEFAULT_LANGUAGE").unwrap_or_else(|_| "de".to_string());
-    let language = lang.as_deref().unwrap_or(&default_language);
+    module_type: &Option<String>,
+) -> Result<Vec<Module>, ApiError> {
+    let languages = Languages::resolve(lang)?;
+    let year = year
+        .as_deref()
+        .map(|year| {
+            year.parse::<i64>()
+                .ok()
+                .filter(|year| *year > 0)
+                .ok_or_else(|| {
+                    ApiError::InvalidYear(format!("Year '{}' is not a positive number", year))
+                })
+        })
+        .transpose()?;
 
     let cache_key = format!(
         "modules_{}_{}_{}",
-        language,
-        year.as_deref().unwrap_or("default"),
+        languages.key(),
+        year.map(|year| year.to_string())
+            .unwrap_or_else(|| "default".to_string()),
         job_id.as_deref().unwrap_or("default")
     );
 
-    get_cached_data(&cache_key, || async {
-        let token = auth::get_token().await?;
-        let client = reqwest::Client::new();
+    let job_id = job_id.clone();
+    let modules = get_cached_data(Resource::ModuleList, &cache_key, move || async move {
         let api_id = job::get_api_id(job_id.as_deref().unwrap_or("")).await?;
-        let url = match api_id {
-            Some(id) => format!(
-                "https://ictbb.crm17.dynamics.com/api/data/v9.1/beembk_modulmappings?$filter=beembk_Abschluss/beembk_abschlussid%20eq%20%27{}%27&$expand=beembk_Lernort,beembk_Modul,beembk_Modultyp,beembk_Level",
-                id
-            ),
-            None => String::from(
-                "https://ictbb.crm17.dynamics.com/api/data/v9.1/beembk_modulmappings?$expand=beembk_Lernort,beembk_Modul,beembk_Modultyp,beembk_Level"
-            ),
-        };
-
-        let res = client.get(url).bearer_auth(token).send().await?;
-        let api_response: ApiResponse = res.json().await?;
-        let re = Regex::new(r"^\d+").unwrap();
-        
-
-        use std::collections::HashMap;
-        let mut modules_by_number: HashMap<i64, Vec<&Value>> = HashMap::new();
-
-        for module in api_response.value.iter() {
-            if let Some(number) = module["beembk_Modul"]["beembk_modulnummer"]
-                .as_str()
-                .and_then(|n| n.parse::<i64>().ok())
-            {
-                modules_by_number.entry(number).or_default().push(module);
-            }
-        }
 
-        let filtered_modules: Vec<Value> = modules_by_number
-            .into_iter()
-            .filter_map(|(_, mut modules)| {
-                modules.sort_by(|a, b| {
-                    let version_a = a["beembk_Modul"]["versionnumber"].as_f64().unwrap_or(0.0);
-                    let version_b = b["beembk_Modul"]["versionnumber"].as_f64().unwrap_or(0.0);
-                    version_b
-                        .partial_cmp(&version_a)
-                        .unwrap_or(std::cmp::Ordering::Equal)
-                });
+        let mut query = ODataQuery::new().expand(&[
+            "beembk_Lernort",
+            "beembk_Modul",
+            "beembk_Modultyp",
+            "beembk_Level",
+        ]);
+        if let Some(id) = api_id {
+            query = query.filter(format!(
+                "beembk_Abschluss/beembk_abschlussid eq {}",
+                upstream::string_literal(&id)
+            ));
+        }
 
-                let module = modules.first()?;
+        let mappings = upstream::modulmappings(&query).await?;
 
-                let level_name = module["beembk_Level"]["beembk_levelname"]
-                    .as_str()
-                    .unwrap_or("");
-                let module_year = re
-                    .find(level_name)
-                    .and_then(|m| m.as_str().parse::<i64>().ok())
-                    .unwrap_or_default();
+        let filtered_modules: Vec<Module> = newest_per_module(&mappings)
+            .into_iter()
+            .filter_map(|(number, mapping)| {
+                let modul = mapping.modul.as_ref()?;
+                let module_year = level_year(mapping);
 
                 if let Some(year) = year {
-                    if module_year != year.parse::<i64>().unwrap_or_default() {
+                    if module_year != year {
                         return None;
                     }
                 }
 
-                let (type_key, title_key, description_key) = match language {
-                    "de" => (
-                        "beembk_lernortname",
-                        "beembk_modultitel",
-                        "beembk_objektbeschreibung",
-                    ),
-                    "fr" => (
-                        "beembk_lernortname_fr",
-                        "beembk_modultitel_fr",
-                        "beembk_objektbeschreibung_fr",
-                    ),
-                    "it" => (
-                        "beembk_lernortname_it",
-                        "beembk_modultitel_it",
-                        "beembk_objektbeschreibung_it",
-                    ),
-                    _ => return None,
-                };
-
-                let number = module["beembk_Modul"]["beembk_modulnummer"]
-                    .as_str()
-                    .unwrap_or("")
-                    .parse::<i64>()
-                    .unwrap_or_default();
-                let name = module["beembk_Modul"][title_key].as_str().unwrap_or("");
-                let version = module["beembk_Modul"]["beembk_version"]
-                    .as_i64()
-                    .unwrap_or_default();
-                let last_modified = module["beembk_Modul"]["modifiedon"].as_str().unwrap_or("");
-                let creation_date = module["beembk_Modul"]["createdon"].as_str().unwrap_or("");
-                let r#type = module["beembk_Lernort"][type_key]
-                    .as_str()
-                    .unwrap_or("")
-                    .chars()
-                    .next()
-                    .map(|c| c.to_uppercase().collect::<String>() + &module["beembk_Lernort"][type_key].as_str().unwrap_or("").chars().skip(1).collect::<String>())
-                    .unwrap_or_default();
-                let description = module["beembk_Modul"][description_key]
-                    .as_str()
-                    .unwrap_or("");
-
-                Some(json!({
-                    "number": number,
-                    "description": description,
-                    "name": name,
-                    "year": module_year,
-                    "version": version,
-                    "last_modified": last_modified,
-                    "creation_date": creation_date,
-                    "type": r#type,
-                }))
+                let r#type = languages.text(|language| {
+                    capitalize(
+                        mapping
+                            .lernort
+                            .as_ref()
+                            .map(|lernort| lernort.name(language))
+                            .unwrap_or(""),
+                    )
+                });
+
+                Some(Module {
+                    number,
+                    description: languages.text(|language| modul.description(language)),
+                    name: languages.text(|language| modul.title(language)),
+                    year: module_year,
+                    version: modul.version.unwrap_or_default(),
+                    last_modified: modul.modified_on.clone().unwrap_or_default(),
+                    creation_date: modul.created_on.clone().unwrap_or_default(),
+                    r#type,
+                    module_type: module_type_of(mapping, &languages),
+                    mandatory: is_mandatory(mapping),
+                })
             })
             .collect();
 
-        Ok(json!(filtered_modules))
-    }).await
+        Ok(filtered_modules)
+    })
+    .await?;
+
+    // Filtered after the cache so every module type shares one cached list.
+    Ok(match module_type {
+        Some(wanted) => modules
+            .into_iter()
+            .filter(|module| {
+                module
+                    .module_type
+                    .as_ref()
+                    .is_some_and(|module_type| module_type.id.eq_ignore_ascii_case(wanted))
+            })
+            .collect(),
+        None => modules,
+    })
 }
 
+/// With a `job_id`, year, place and module type are the ones of that job's usage.
 pub async fn get_module(
     id: &str,
     lang: &Option<String>,
-) -> Result<Value, Box<dyn std::error::Error>> {
-    let default_language = env::var("DEFAULT_LANGUAGE").unwrap_or_else(|_| "de".to_string());
-    let language = lang.as_deref().unwrap_or(&default_language);
-    let cache_key = format!("module_{}_{}", id, language);
-
-    get_cached_data(&cache_key, || async {
-        let token = auth::get_token().await?;
-        let client = reqwest::Client::new();
-
-        let url = format!(
-            "https://ictbb.crm17.dynamics.com/api/data/v9.1/beembk_moduls?$filter=contains(beembk_modulnummer,'{}')",
-            id
-        );
-
-        let res = client.get(&url).bearer_auth(token).send().await?;
-        let api_response: ApiResponse = res.json().await?;
-
-        if api_response.value.is_empty() {
-            return Err("Module not found".into());
-        }
+    job_id: &Option<String>,
+) -> Result<ModuleDetail, ApiError> {
+    let id = parse_module_number(id)?;
+    let languages = Languages::resolve(lang)?;
+    let cache_key = format!("module_{}_{}", id, languages.key());
+    // Rejects unknown jobs before anything is fetched.
+    let job_id = job_id.as_deref().unwrap_or("");
+    job::get_api_id(job_id).await?;
+
+    let mut module = get_cached_data(Resource::ModuleDetail, &cache_key, move || async move {
+        let query = ODataQuery::new().filter(format!(
+            "beembk_modulnummer eq {}",
+            upstream::string_literal(&id)
+        ));
+        let moduls = upstream::moduls(&query).await?;
+
+        let module = moduls
+            .iter()
+            .max_by_key(|modul| modul.row_version.unwrap_or(0))
+            .ok_or_else(|| ApiError::NotFound(format!("Module {} not found", id)))?;
+
+        let (place, usages) = get_module_detail(&id, &languages).await?;
+        let objectives = get_module_objectives(&id, &languages).await?;
+
+        Ok(ModuleDetail {
+            number: module.number().unwrap_or_default(),
+            description: languages.text(|language| module.description(language)),
+            name: languages.text(|language| module.title(language)),
+            year: place.year,
+            r#type: place.r#type,
+            module_type: place.module_type,
+            mandatory: place.mandatory,
+            version: module.version.unwrap_or_default(),
+            last_modified: module.modified_on.clone().unwrap_or_default(),
+            creation_date: module.created_on.clone().unwrap_or_default(),
+            pdf: languages
+                .text(|language| format!("{}{}", config::pdf_base_url(), module.pdf(language))),
+            competence: languages.text(|language| module.competence(language)),
+            objectives,
+            usages,
+        })
+    })
+    .await?;
 
-        let module = api_response
-            .value
+    if !job_id.is_empty() {
+        let usage = module
+            .usages
             .iter()
-            .max_by_key(|module| module["versionnumber"].as_i64().unwrap_or(0))
-            .ok_or("No modules found")?;
-
-        let module_detail = get_module_detail(id, language).await?;
-        let year = module_detail["year"].as_i64().unwrap_or_default();
-        let r#type = module_detail["type"].as_str().unwrap_or("").to_string();
-
-        let (title_key, description_key, competence_key, pdf_key) = match language {
-            "de" => (
-                "beembk_modultitel",
-                "beembk_objektbeschreibung",
-                "beembk_kompetenz",
-                "beembk_pdfname_de",
-            ),
-            "fr" => (
-                "beembk_modultitel_fr",
-                "beembk_objektbeschreibung_fr",
-                "beembk_kompetenz_fr",
-                "beembk_pdfname_fr",
-            ),
-            "it" => (
-                "beembk_modultitel_it",
-                "beembk_objektbeschreibung_it",
-                "beembk_kompetenz_it",
-                "beembk_pdfname_it",
-            ),
-            _ => return Err("Unsupported language".into()),
-        };
-
-        let number = module["beembk_modulnummer"]
-            .as_str()
-            .unwrap_or("")
-            .parse::<i64>()
-            .unwrap_or_default();
-
-        let name = module[title_key].as_str().unwrap_or("").to_string();
-        let version = module["beembk_version"].as_i64().unwrap_or_default();
-        let last_modified = module["modifiedon"].as_str().unwrap_or("").to_string();
-        let creation_date = module["createdon"].as_str().unwrap_or("").to_string();
-        let description = module[description_key].as_str().unwrap_or("").to_string();
-        let competence = module[competence_key].as_str().unwrap_or("").to_string();
-        let pdf = module[pdf_key].as_str().unwrap_or("").to_string();
-
-        let objectives = get_module_objectives(id, language).await?;
-
-        Ok(json!({
-            "number": number,
-            "description": description,
-            "name": name,
-            "year": year,
-            "type": r#type,
-            "version": version,
-            "last_modified": last_modified,
-            "creation_date": creation_date,
-            "pdf": format!("https://www.modulbaukasten.ch/Module/{}", pdf),
-            "competence": competence,
-            "objectives": objectives,
-        }))
-    }).await
+            .find(|usage| usage.job.id == job_id)
+            .ok_or_else(|| {
+                ApiError::NotFound(format!(
+                    "Module {} is not part of job {}",
+                    module.number, job_id
+                ))
+            })?;
+
+        module.year = usage.place.year;
+        module.r#type = usage.place.r#type.clone();
+        module.module_type = usage.place.module_type.clone();
+        module.mandatory = usage.place.mandatory;
+    }
+
+    Ok(module)
 }
 
+/// The place of the most recent mapping, and every job using the newest module version.
 async fn get_module_detail(
     id: &str,
-    lang: &str,
-) -> Result<Value, Box<dyn std::error::Error>> {
-    let token = auth::get_token().await?;
-    let client = reqwest::Client::new();
-
-    let url = format!(
-            "https://ictbb.crm17.dynamics.com/api/data/v9.1/beembk_modulmappings?$filter=beembk_Modul/beembk_modulnummer%20eq%20'{}'&$expand=beembk_Lernort,beembk_Modul,beembk_Modultyp,beembk_Level",
-            id
-        );
-    let res = client.get(&url).bearer_auth(token).send().await?;
-    let api_response: ApiResponse = res.json().await?;
-
-    if api_response.value.is_empty() {
-        return Err("Module not found".into());
-    }
+    languages: &Languages,
+) -> Result<(LearningPlace, Vec<ModuleUsage>), ApiError> {
+    let query = ODataQuery::new()
+        .filter(format!(
+            "beembk_Modul/beembk_modulnummer eq {}",
+            upstream::string_literal(id)
+        ))
+        .expand(&[
+            "beembk_Lernort",
+            "beembk_Modul",
+            "beembk_Modultyp",
+            "beembk_Level",
+            "beembk_Abschluss",
+        ]);
+    let mappings = upstream::modulmappings(&query).await?;
+
+    let mapping = mappings
+        .iter()
+        .max_by_key(|mapping| mapping.row_version.unwrap_or(0))
+        .ok_or_else(|| ApiError::NotFound(format!("Module {} not found", id)))?;
+
+    let modul_version = |mapping: &ModulMapping| {
+        mapping
+            .modul
+            .as_ref()
+            .and_then(|modul| modul.row_version)
+            .unwrap_or(0)
+    };
+    let newest = mappings.iter().map(modul_version).max().unwrap_or(0);
+    let jobs = job::jobs_by_api_id(languages).await?;
 
-    let module = api_response
-        .value
+    let mut usages: Vec<ModuleUsage> = mappings
         .iter()
-        .max_by_key(|module| module["versionnumber"].as_i64().unwrap_or(0))
-        .ok_or("No modules found")?;
+        .filter(|mapping| modul_version(mapping) == newest)
+        .filter_map(|mapping| {
+            let api_id = mapping.abschluss.as_ref()?.id.as_deref()?;
+            Some(ModuleUsage {
+                job: jobs.get(api_id)?.clone(),
+                place: learning_place(mapping, languages),
+            })
+        })
+        .collect();
+    usages.sort_by(|a, b| (&a.job.id, a.place.year).cmp(&(&b.job.id, b.place.year)));
 
-    let re = Regex::new(r"^\d+").map_err(|e| format!("Invalid regex: {}", e))?;
-    let level_name = module["beembk_Level"]["beembk_levelname"]
-        .as_str()
-        .unwrap_or("");
-    let module_year = re
-        .find(level_name)
-        .and_then(|m| m.as_str().parse::<i64>().ok())
-        .unwrap_or_default();
+    Ok((learning_place(mapping, languages), usages))
+}
 
-    let type_key = match lang {
-        "de" => "beembk_lernortname",
-        "fr" => "beembk_lernortname_fr",
-        "it" => "beembk_lernortname_it",
-        _ => return Err("Unsupported language".into()),
-    };
+fn learning_place(mapping: &ModulMapping, languages: &Languages) -> LearningPlace {
+    let r#type = languages.text(|language| {
+        mapping
+            .lernort
+            .as_ref()
+            .map(|lernort| lernort.name(language))
+            .unwrap_or("")
+    });
+
+    LearningPlace {
+        year: level_year(mapping),
+        r#type,
+        module_type: module_type_of(mapping, languages),
+        mandatory: is_mandatory(mapping),
+    }
+}
 
-    let r#type = module["beembk_Lernort"][type_key].as_str().unwrap_or("");
+/// Objectives of module `id`, which must exist in `beembk_moduls`.
+pub async fn get_objectives(id: &str, lang: &Option<String>) -> Result<Vec<Objective>, ApiError> {
+    let id = parse_module_number(id)?;
+    let languages = Languages::resolve(lang)?;
+    let cache_key = format!("objectives_{}_{}", id, languages.key());
+
+    get_cached_data(Resource::Objectives, &cache_key, move || async move {
+        let query = ODataQuery::new()
+            .select(&["beembk_modulid"])
+            .filter(format!(
+                "beembk_modulnummer eq {}",
+                upstream::string_literal(&id)
+            ))
+            .top(1);
+        if upstream::moduls(&query).await?.is_empty() {
+            return Err(ApiError::NotFound(format!("Module {} not found", id)));
+        }
 
-    Ok(json!({
-        "year": module_year,
-        "type": r#type,
-    }))
+        fetch_module_objectives(id, languages).await
+    })
+    .await
 }
 
+/// Objective `{id}.{n}`, served from the cached objectives of the module.
+pub async fn get_objective(
+    id: &str,
+    n: &str,
+    lang: &Option<String>,
+) -> Result<Objective, ApiError> {
+    if n.is_empty() || !n.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(ApiError::InvalidObjectiveNumber(format!(
+            "Objective number '{}' must be a number such as 2",
+            n
+        )));
+    }
+    let number = format!("{}.{}", parse_module_number(id)?, n);
+    let objectives = get_objectives(id, lang).await?;
+
+    objectives
+        .into_iter()
+        .find(|objective| objective.number == number)
+        .ok_or_else(|| ApiError::NotFound(format!("Objective {} not found", number)))
+}
+
+/// Objectives of a module the caller already found, cached like `get_objectives`.
 pub async fn get_module_objectives(
     id: &str,
-    lang: &str,
-) -> Result<Value, Box<dyn std::error::Error>> {
-    let token = auth::get_token().await?;
-    let client = reqwest::Client::new();
-
-    let url = format!(
-        "https://ictbb.crm17.dynamics.com/api/data/v9.1/beembk_handlungsziels?$filter=startswith(beembk_handlungszielnr,'{}')",
-        id
-    );
-    let res = client.get(&url).bearer_auth(&token).send().await?;
-    let api_response: ApiResponse = res.json().await?;
+    languages: &Languages,
+) -> Result<Vec<Objective>, ApiError> {
+    let id = parse_module_number(id)?;
+    let cache_key = format!("objectives_{}_{}", id, languages.key());
+
+    let languages = languages.clone();
+    get_cached_data(Resource::Objectives, &cache_key, move || {
+        fetch_module_objectives(id, languages)
+    })
+    .await
+}
 
-    let details_url = format!(
-        "https://ictbb.crm17.dynamics.com/api/data/v9.1/beembk_handlungsnotwendigeskenntnises?$filter=startswith(beembk_hanoknr,'{}')",
-        id
-    );
-    let details_res = client.get(&details_url).bearer_auth(&token).send().await?;
-    let details_api_response: ApiResponse = details_res.json().await?;
-
-    let (detail_key, name_key) = match lang {
-        "de" => ("beembk_hanok", "beembk_handlungsziel"),
-        "fr" => ("beembk_hanok_fr", "beembk_handlungsziel_fr"),
-        "it" => ("beembk_hanok_it", "beembk_handlungsziel_it"),
-        _ => return Err("Unsupported language".into()),
-    };
+async fn fetch_module_objectives(
+    id: String,
+    languages: Languages,
+) -> Result<Vec<Objective>, ApiError> {
+    // The trailing dot keeps module 11 from matching the objectives of 110 to 119.
+    let prefix = format!("{}.", id);
+
+    let query = ODataQuery::new().filter(format!(
+        "startswith(beembk_handlungszielnr,{})",
+        upstream::string_literal(&prefix)
+    ));
+    let handlungsziels = upstream::handlungsziels(&query).await?;
+
+    let details_query = ODataQuery::new().filter(format!(
+        "startswith(beembk_hanoknr,{})",
+        upstream::string_literal(&prefix)
+    ));
+    let kenntnisse = upstream::handlungsnotwendigeskenntnises(&details_query).await?;
+
+    let mut details: Vec<Knowledge> = kenntnisse
+        .iter()
+        .filter_map(|detail| {
+            Some(Knowledge {
+                number: detail.number.clone()?,
+                text: languages.text(|language| detail.text(language)),
+            })
+        })
+        .collect();
+    details.sort_by_key(|detail| number_key(&detail.number));
 
-    let objectives: Vec<Value> = api_response
-        .value
+    let mut objectives: Vec<Objective> = handlungsziels
         .iter()
-        .enumerate()
-        .map(|(objectives_index, objective)| {
-            let name = objective
-                .get(name_key)
-                .and_then(|v| v.as_str())
-                .unwrap_or("")
-                .to_string();
-
-            let details: Vec<Value> = details_api_response
-                .value
+        .filter_map(|objective| {
+            let number = objective.number.clone()?;
+            let details = details
                 .iter()
-                .filter_map(|detail| {
-                    let detail_nr = detail.get("beembk_hanoknr").and_then(|v| v.as_str())?;
-                    if detail_nr.starts_with(&format!("{}.{}", id, objectives_index + 1)) {
-                        let detail_name = detail
-                            .get(detail_key)
-                            .and_then(|v| v.as_str())
-                            .unwrap_or("")
-                            .to_string();
-                        Some(json!(detail_name + " " + detail_nr))
-                    } else {
-                        None
-                    }
+                .filter(|detail| {
+                    detail
+                        .number
+                        .rsplit_once('.')
+                        .is_some_and(|(parent, _)| parent == number)
                 })
+                .cloned()
                 .collect();
 
-            json!({
-                "name": name,
-                "details": details
+            Some(Objective {
+                name: languages.text(|language| objective.name(language)),
+                number,
+                details,
             })
         })
         .collect();
+    objectives.sort_by_key(|objective| number_key(&objective.number));
+
+    Ok(objectives)
+}
+
+/// Orders dotted numbers numerically, so `110.2` comes before `110.10`.
+fn number_key(number: &str) -> Vec<u64> {
+    number
+        .split('.')
+        .map(|part| part.trim().parse().unwrap_or(u64::MAX))
+        .collect()
+}
+
+/// Picks, for each module number, the mapping of the newest module version.
+pub(crate) fn newest_per_module(mappings: &[ModulMapping]) -> Vec<(i64, &ModulMapping)> {
+    let mut modules_by_number: HashMap<i64, Vec<&ModulMapping>> = HashMap::new();
+
+    for mapping in mappings.iter() {
+        if let Some(number) = mapping.modul.as_ref().and_then(|modul| modul.number()) {
+            modules_by_number.entry(number).or_default().push(mapping);
+        }
+    }
+
+    modules_by_number
+        .into_iter()
+        .filter_map(|(number, mappings)| {
+            // `min_by_key` keeps the first of equally new mappings.
+            let mapping = mappings.into_iter().min_by_key(|mapping| {
+                std::cmp::Reverse(
+                    mapping
+                        .modul
+                        .as_ref()
+                        .and_then(|modul| modul.row_version)
+                        .unwrap_or(0),
+                )
+            })?;
+            Some((number, mapping))
+        })
+        .collect()
+}
+
+/// Accepts only plain module numbers such as `117`, normalised without leading zeros.
+fn parse_module_number(id: &str) -> Result<String, ApiError> {
+    id.bytes()
+        .all(|b| b.is_ascii_digit())
+        .then(|| id.parse::<u32>().ok())
+        .flatten()
+        .filter(|number| *number > 0)
+        .map(|number| number.to_string())
+        .ok_or_else(|| {
+            ApiError::InvalidModuleId(format!(
+                "Module id '{}' must be a module number such as 117",
+                id
+            ))
+        })
+}
+
+pub(crate) fn module_type_of(mapping: &ModulMapping, languages: &Languages) -> Option<ModuleType> {
+    let modultyp = mapping.modultyp.as_ref()?;
 
-    Ok(json!(objectives))
+    Some(ModuleType {
+        id: modultyp.name_de.as_deref()?.to_lowercase(),
+        name: languages.text(|language| modultyp.name(language)),
+    })
 }
 
-// async fn get_module_courses(id: &str, lang: &str) -> Result<Value, Box<dyn std::error::Error>> {
-//     let token = auth::get_token().await?;
-//     let client = reqwest::Client::new();
+pub(crate) fn is_mandatory(mapping: &ModulMapping) -> bool {
+    mapping
+        .modultyp
+        .as_ref()
+        .is_some_and(|modultyp| modultyp.is_mandatory())
+}
 
-//     let url = format!(
-//         "https://ictbb.crm17.dynamics.com/api/data/v9.1/beembk_modulmappings?$filter=beembk_Modul/beembk_modulid%20eq%20%2714ac5969-0678-eb11-a812-000d3a831967%27&$expand=beembk_Abschluss,'{}')",
-//         id
-//     );
+pub(crate) fn level_year(mapping: &ModulMapping) -> i64 {
+    static LEADING_DIGITS: OnceLock<Regex> = OnceLock::new();
 
-//     println!("{:?}", url);
+    let re = LEADING_DIGITS.get_or_init(|| Regex::new(r"^\d+").unwrap());
+    let level_name = mapping
+        .level
+        .as_ref()
+        .and_then(|level| level.name.as_deref())
+        .unwrap_or("");
 
-//     let res = client.get(&url).bearer_auth(token).send().await?;
-//     let api_response: ApiResponse = res.json().await?;
+    re.find(level_name)
+        .and_then(|m| m.as_str().parse::<i64>().ok())
+        .unwrap_or_default()
+}
 
-//     println!("{:?}", api_response);
+pub(crate) fn capitalize(value: &str) -> String {
+    let mut chars = value.chars();
 
-//     Ok(json!(courses))
-// }
+    match chars.next() {
+        Some(first) => first.to_uppercase().collect::<String>() + chars.as_str(),
+        None => String::new(),
+    }
+}
@@ -0,0 +1,91 @@
+use crate::config;
+use crate::error::ApiError;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Recording {
+    pub url: String,
+    pub status: u16,
+    pub body: String,
+}
+
+/// Reduces an upstream URL to its path and sorted query, so recordings replay
+/// regardless of the configured host and of query parameter order.
+pub fn normalize_url(url: &str) -> String {
+    let Ok(url) = reqwest::Url::parse(url) else {
+        return url.to_string();
+    };
+
+    let mut pairs: Vec<(String, String)> = url
+        .query_pairs()
+        .map(|(key, value)| (key.into_owned(), value.into_owned()))
+        .collect();
+    pairs.sort();
+
+    if pairs.is_empty() {
+        return url.path().to_string();
+    }
+
+    let query = pairs
+        .iter()
+        .map(|(key, value)| format!("{}={}", key, value))
+        .collect::<Vec<_>>()
+        .join("&");
+
+    format!("{}?{}", url.path(), query)
+}
+
+// FNV-1a, stable across builds unlike `DefaultHasher`.
+fn fingerprint(value: &str) -> u64 {
+    value.bytes().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+/// File name for a normalised URL: a readable slug plus a hash to keep keys unique.
+pub fn recording_path(url: &str) -> PathBuf {
+    let normalized = normalize_url(url);
+    let slug: String = normalized
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .take(80)
+        .collect();
+
+    PathBuf::from(config::upstream_record_dir()).join(format!(
+        "{}-{:016x}.json",
+        slug.trim_matches('_'),
+        fingerprint(&normalized)
+    ))
+}
+
+pub fn load(url: &str) -> Result<Recording, ApiError> {
+    let path = recording_path(url);
+    let content = fs::read_to_string(&path).map_err(|_| {
+        ApiError::UpstreamUnavailable(format!(
+            "No recording for {} in {}",
+            normalize_url(url),
+            path.display()
+        ))
+    })?;
+
+    serde_json::from_str(&content)
+        .map_err(|err| ApiError::Internal(format!("Invalid recording {}: {}", path.display(), err)))
+}
+
+pub fn save(recording: &Recording) -> Result<(), ApiError> {
+    let path = recording_path(&recording.url);
+    let write = || -> std::io::Result<()> {
+        fs::create_dir_all(config::upstream_record_dir())?;
+        fs::write(&path, serde_json::to_string_pretty(recording)?)
+    };
+
+    write().map_err(|err| {
+        ApiError::Internal(format!(
+            "Failed to write recording {}: {}",
+            path.display(),
+            err
+        ))
+    })
+}
@@ -1,7 +1,13 @@
+use crate::cache;
+use crate::config;
+use crate::curriculum::get_curriculum;
 use crate::documentation::get_documentation;
-use crate::job::get_jobs;
-use crate::module::{get_module, get_modules};
-use actix_web::{get, web, HttpResponse, Responder};
+use crate::error::ApiError;
+use crate::job::{get_job, get_jobs};
+use crate::module::{get_module, get_modules, get_objective, get_objectives};
+use crate::upstream;
+use crate::warmup::{warm_and_log, Running};
+use actix_web::{delete, get, post, web, HttpRequest, HttpResponse, Responder};
 use serde::Deserialize;
 use serde_json::json;
 
@@ -10,22 +16,21 @@ pub struct QueryParams {
     pub lang: Option<String>,
     pub year: Option<String>,
     pub job_id: Option<String>,
+    pub module_type: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct PurgeParams {
+    pub prefix: Option<String>,
 }
 
 #[get("/")]
-async fn index() -> impl Responder {
-    match get_documentation().await {
-        Ok(documentation) => HttpResponse::Ok()
-            .content_type("application/json")
-            .json(documentation),
-        Err(err) => {
-            eprintln!("Error fetching documentation: {:?}", err);
-
-            return HttpResponse::InternalServerError()
-                .content_type("application/json")
-                .json(json!({ "error": "Error fetching documentation" }));
-        }
-    }
+async fn index() -> Result<HttpResponse, ApiError> {
+    let documentation = get_documentation().await?;
+
+    Ok(HttpResponse::Ok()
+        .content_type("application/json")
+        .json(documentation))
 }
 
 #[get("/jobs/")]
@@ -36,21 +41,36 @@ async fn job_redirect() -> impl Responder {
 }
 
 #[get("/jobs")]
-async fn jobs(query: web::Query<QueryParams>) -> impl Responder {
-    let jobs = match get_jobs(&query.lang).await {
-        Ok(jobs) => jobs,
-        Err(err) => {
-            eprintln!("Error fetching jobs: {:?}", err);
-
-            return HttpResponse::InternalServerError()
-                .content_type("application/json")
-                .json(json!({ "error": "Error fetching jobs" }));
-        }
-    };
+async fn jobs(query: web::Query<QueryParams>) -> Result<HttpResponse, ApiError> {
+    let jobs = get_jobs(&query.lang).await?;
 
-    HttpResponse::Ok()
+    Ok(HttpResponse::Ok()
         .content_type("application/json")
-        .json(jobs)
+        .json(jobs))
+}
+
+#[get("/jobs/{id}")]
+async fn job_by_id(
+    id: web::Path<String>,
+    query: web::Query<QueryParams>,
+) -> Result<HttpResponse, ApiError> {
+    let job = get_job(&id.into_inner(), &query.lang).await?;
+
+    Ok(HttpResponse::Ok()
+        .content_type("application/json")
+        .json(job))
+}
+
+#[get("/jobs/{id}/curriculum")]
+async fn job_curriculum(
+    id: web::Path<String>,
+    query: web::Query<QueryParams>,
+) -> Result<HttpResponse, ApiError> {
+    let curriculum = get_curriculum(&id.into_inner(), &query.lang).await?;
+
+    Ok(HttpResponse::Ok()
+        .content_type("application/json")
+        .json(curriculum))
 }
 
 #[get("/modules/")]
@@ -61,51 +81,145 @@ async fn modules_redirect() -> impl Responder {
 }
 
 #[get("/modules")]
-async fn modules(query: web::Query<QueryParams>) -> impl Responder {
-    let modules = match get_modules(&query.lang, &query.year, &query.job_id).await {
-        Ok(modules) => modules,
-        Err(err) => {
-            eprintln!("Error fetching module: {:?}", err);
-
-            return HttpResponse::InternalServerError()
-                .content_type("application/json")
-                .json(json!({ "error": "Error fetching module" }));
-        }
-    };
+async fn modules(query: web::Query<QueryParams>) -> Result<HttpResponse, ApiError> {
+    let modules = get_modules(&query.lang, &query.year, &query.job_id, &query.module_type).await?;
 
-    HttpResponse::Ok()
+    Ok(HttpResponse::Ok()
         .content_type("application/json")
-        .json(modules)
+        .json(modules))
 }
 
 #[get("/modules/{id}")]
-async fn module_by_id(id: web::Path<String>, query: web::Query<QueryParams>) -> impl Responder {
-    let module = match get_module(&id.into_inner(), &query.lang).await {
-        Ok(module) => module,
-        Err(err) => {
-            eprintln!("Error fetching module: {:?}", err);
-            if err.to_string() == "Module not found" {
-                return HttpResponse::NotFound()
-                    .content_type("application/json")
-                    .json(json!({ "error": "Module not found" }));
-            }
-
-            return HttpResponse::InternalServerError()
-                .content_type("application/json")
-                .json(json!({ "error": "Error fetching module" }));
-        }
-    };
+async fn module_by_id(
+    id: web::Path<String>,
+    query: web::Query<QueryParams>,
+) -> Result<HttpResponse, ApiError> {
+    let module = get_module(&id.into_inner(), &query.lang, &query.job_id).await?;
+
+    Ok(HttpResponse::Ok()
+        .content_type("application/json")
+        .json(module))
+}
+
+#[get("/modules/{id}/objectives")]
+async fn module_objectives(
+    id: web::Path<String>,
+    query: web::Query<QueryParams>,
+) -> Result<HttpResponse, ApiError> {
+    let objectives = get_objectives(&id.into_inner(), &query.lang).await?;
+
+    Ok(HttpResponse::Ok()
+        .content_type("application/json")
+        .json(objectives))
+}
+
+#[get("/modules/{id}/objectives/{n}")]
+async fn module_objective(
+    path: web::Path<(String, String)>,
+    query: web::Query<QueryParams>,
+) -> Result<HttpResponse, ApiError> {
+    let (id, n) = path.into_inner();
+    let objective = get_objective(&id, &n, &query.lang).await?;
 
-    HttpResponse::Ok()
+    Ok(HttpResponse::Ok()
         .content_type("application/json")
-        .json(module)
+        .json(objective))
+}
+
+/// Rejects the request unless it carries `Authorization: Bearer <ADMIN_TOKEN>`.
+fn authorize_admin(req: &HttpRequest) -> Result<(), ApiError> {
+    let Some(expected) = config::admin_token() else {
+        return Err(ApiError::Unauthorized(
+            "Admin routes are disabled, set ADMIN_TOKEN to enable them".to_string(),
+        ));
+    };
+
+    let provided = req
+        .headers()
+        .get("Authorization")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .unwrap_or("");
+
+    if constant_time_eq(provided.as_bytes(), expected.as_bytes()) {
+        Ok(())
+    } else {
+        Err(ApiError::Unauthorized(
+            "Missing or invalid admin token".to_string(),
+        ))
+    }
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+#[get("/admin/cache")]
+async fn admin_cache(req: HttpRequest) -> Result<HttpResponse, ApiError> {
+    authorize_admin(&req)?;
+
+    Ok(HttpResponse::Ok().json(json!({
+        "entries": cache::list_entries().await?,
+        "stats": cache::stats(),
+        "upstream": { "paged_responses": upstream::paged_responses() },
+    })))
+}
+
+/// Purges every entry starting with `prefix`, or the whole cache without one.
+#[delete("/admin/cache")]
+async fn admin_purge(
+    req: HttpRequest,
+    query: web::Query<PurgeParams>,
+) -> Result<HttpResponse, ApiError> {
+    authorize_admin(&req)?;
+
+    let purged = cache::purge_prefix(query.prefix.as_deref().unwrap_or("")).await?;
+
+    Ok(HttpResponse::Ok().json(json!({ "purged": purged })))
+}
+
+#[delete("/admin/cache/{key}")]
+async fn admin_purge_key(
+    req: HttpRequest,
+    key: web::Path<String>,
+) -> Result<HttpResponse, ApiError> {
+    authorize_admin(&req)?;
+
+    if !cache::purge_key(&key).await? {
+        return Err(ApiError::NotFound(format!(
+            "Cache entry '{}' not found",
+            key
+        )));
+    }
+
+    Ok(HttpResponse::Ok().json(json!({ "purged": 1 })))
+}
+
+#[post("/admin/cache/warmup")]
+async fn admin_warmup(req: HttpRequest) -> Result<HttpResponse, ApiError> {
+    authorize_admin(&req)?;
+
+    let Some(running) = Running::claim() else {
+        return Ok(HttpResponse::Accepted().json(json!({ "status": "already_running" })));
+    };
+    actix_web::rt::spawn(warm_and_log(running));
+
+    Ok(HttpResponse::Accepted().json(json!({ "status": "started" })))
 }
 
 pub fn config(cfg: &mut web::ServiceConfig) {
     cfg.service(index)
         .service(module_by_id)
+        .service(module_objectives)
+        .service(module_objective)
         .service(modules)
         .service(modules_redirect)
         .service(jobs)
-        .service(job_redirect);
+        .service(job_redirect)
+        .service(job_by_id)
+        .service(job_curriculum)
+        .service(admin_cache)
+        .service(admin_purge)
+        .service(admin_purge_key)
+        .service(admin_warmup);
 }
@@ -0,0 +1,503 @@
+use crate::auth;
+use crate::config::{self, UpstreamMode};
+use crate::error::ApiError;
+use crate::model::Language;
+use crate::recording::{self, Recording};
+use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use std::env;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+const DEFAULT_PAGE_SIZE: u32 = 5000;
+
+// Characters OData expressions rely on that are safe to leave unescaped in a query value.
+const QUERY_VALUE: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'_')
+    .remove(b'.')
+    .remove(b'~')
+    .remove(b'/')
+    .remove(b',')
+    .remove(b'(')
+    .remove(b')');
+
+static PAGED_RESPONSES: AtomicU64 = AtomicU64::new(0);
+
+thread_local! {
+    // Pooled connections are driven by the runtime that opened them, so every
+    // worker thread (each with its own runtime) keeps its own pool.
+    static CLIENT: reqwest::Client = reqwest::Client::builder()
+        .user_agent(concat!("api-ict/", env!("CARGO_PKG_VERSION")))
+        .connect_timeout(Duration::from_secs(10))
+        .timeout(Duration::from_secs(60))
+        .pool_idle_timeout(Duration::from_secs(90))
+        .build()
+        .expect("Failed to build HTTP client");
+}
+
+/// HTTP client of the current worker, so connections to the upstream are pooled across requests.
+pub fn client() -> reqwest::Client {
+    CLIENT.with(reqwest::Client::clone)
+}
+
+#[derive(Debug, Clone)]
+pub struct UpstreamResponse {
+    pub status: u16,
+    pub body: String,
+}
+
+impl UpstreamResponse {
+    pub fn error_for_status(self) -> Result<Self, ApiError> {
+        match self.status {
+            200..=299 => Ok(self),
+            401 | 403 => Err(ApiError::UpstreamAuth(format!(
+                "Upstream rejected the request with status {}",
+                self.status
+            ))),
+            status => Err(ApiError::UpstreamUnavailable(format!(
+                "Upstream responded with status {}",
+                status
+            ))),
+        }
+    }
+
+    pub fn json<T: DeserializeOwned>(&self) -> Result<T, ApiError> {
+        serde_json::from_str(&self.body).map_err(|err| {
+            ApiError::UpstreamUnavailable(format!("Invalid upstream response: {}", err))
+        })
+    }
+}
+
+/// Sends `request` according to `UPSTREAM_MODE`: live, live while recording the
+/// response, or served from the recordings without touching the network.
+pub async fn execute(request: reqwest::RequestBuilder) -> Result<UpstreamResponse, ApiError> {
+    let request = request
+        .build()
+        .map_err(|err| ApiError::Internal(format!("Invalid upstream request: {}", err)))?;
+    let url = request.url().to_string();
+    let mode = config::upstream_mode();
+
+    if mode == UpstreamMode::Replay {
+        let recording = recording::load(&url)?;
+        return Ok(UpstreamResponse {
+            status: recording.status,
+            body: recording.body,
+        });
+    }
+
+    let res = client().execute(request).await?;
+    let response = UpstreamResponse {
+        status: res.status().as_u16(),
+        body: res.text().await?,
+    };
+
+    if mode == UpstreamMode::Record {
+        recording::save(&Recording {
+            url: recording::normalize_url(&url),
+            status: response.status,
+            body: response.body.clone(),
+        })?;
+    }
+
+    Ok(response)
+}
+
+/// Quotes `value` as an OData string literal, doubling embedded single quotes.
+pub fn string_literal(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ODataQuery {
+    filter: Vec<String>,
+    expand: Vec<String>,
+    select: Vec<String>,
+    orderby: Vec<String>,
+    top: Option<u32>,
+}
+
+impl ODataQuery {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a `$filter` expression; multiple filters are combined with `and`.
+    pub fn filter(mut self, expression: impl Into<String>) -> Self {
+        self.filter.push(expression.into());
+        self
+    }
+
+    pub fn expand(mut self, navigation_properties: &[&str]) -> Self {
+        self.expand
+            .extend(navigation_properties.iter().map(|p| p.to_string()));
+        self
+    }
+
+    pub fn select(mut self, properties: &[&str]) -> Self {
+        self.select.extend(properties.iter().map(|p| p.to_string()));
+        self
+    }
+
+    pub fn orderby(mut self, expression: impl Into<String>) -> Self {
+        self.orderby.push(expression.into());
+        self
+    }
+
+    pub fn top(mut self, top: u32) -> Self {
+        self.top = Some(top);
+        self
+    }
+
+    pub fn to_query_string(&self) -> String {
+        let mut params = Vec::new();
+
+        if !self.filter.is_empty() {
+            let filter = if self.filter.len() == 1 {
+                self.filter[0].clone()
+            } else {
+                self.filter
+                    .iter()
+                    .map(|f| format!("({})", f))
+                    .collect::<Vec<_>>()
+                    .join(" and ")
+            };
+            params.push(("$filter", filter));
+        }
+        if !self.expand.is_empty() {
+            params.push(("$expand", self.expand.join(",")));
+        }
+        if !self.select.is_empty() {
+            params.push(("$select", self.select.join(",")));
+        }
+        if !self.orderby.is_empty() {
+            params.push(("$orderby", self.orderby.join(",")));
+        }
+        if let Some(top) = self.top {
+            params.push(("$top", top.to_string()));
+        }
+
+        params
+            .into_iter()
+            .map(|(key, value)| format!("{}={}", key, utf8_percent_encode(&value, QUERY_VALUE)))
+            .collect::<Vec<_>>()
+            .join("&")
+    }
+
+    pub fn url(&self, entity_set: &str) -> String {
+        let query = self.to_query_string();
+
+        if query.is_empty() {
+            format!("{}/{}", config::dynamics_base_url(), entity_set)
+        } else {
+            format!("{}/{}?{}", config::dynamics_base_url(), entity_set, query)
+        }
+    }
+}
+
+/// Number of upstream queries that returned more than one page since startup.
+pub fn paged_responses() -> u64 {
+    PAGED_RESPONSES.load(Ordering::Relaxed)
+}
+
+fn page_size() -> u32 {
+    env::var("ODATA_PAGE_SIZE")
+        .ok()
+        .and_then(|size| size.parse().ok())
+        .filter(|size| *size > 0)
+        .unwrap_or(DEFAULT_PAGE_SIZE)
+}
+
+#[derive(Debug, Deserialize)]
+struct ODataResponse<T> {
+    value: Vec<T>,
+    #[serde(rename = "@odata.nextLink")]
+    next_link: Option<String>,
+}
+
+/// The bearer token goes along with every page, so only links to our upstream are
+/// followed. Replayed recordings never reach the network and may name any host.
+fn trusted_next_link(next_link: &str) -> bool {
+    config::upstream_mode() == UpstreamMode::Replay
+        || next_link.starts_with(&format!("{}/", config::dynamics_base_url()))
+}
+
+/// Fetches every row of `entity_set`, following `@odata.nextLink` until the last page.
+async fn fetch<T: DeserializeOwned>(
+    entity_set: &str,
+    query: &ODataQuery,
+) -> Result<Vec<T>, ApiError> {
+    let prefer = format!("odata.maxpagesize={}", page_size());
+    let mut url = query.url(entity_set);
+    let mut rows = Vec::new();
+    let mut pages = 0;
+
+    loop {
+        let res = auth::send_authorized(client().get(&url).header("Prefer", &prefer)).await?;
+        let response: ODataResponse<T> = res.json()?;
+
+        rows.extend(response.value);
+        pages += 1;
+
+        match response.next_link {
+            Some(next_link) if trusted_next_link(&next_link) => url = next_link,
+            Some(next_link) => {
+                return Err(ApiError::UpstreamUnavailable(format!(
+                    "{} returned a nextLink outside the Dynamics API: {}",
+                    entity_set, next_link
+                )))
+            }
+            None => break,
+        }
+    }
+
+    if pages > 1 {
+        PAGED_RESPONSES.fetch_add(1, Ordering::Relaxed);
+        eprintln!(
+            "Warning: {} returned {} rows across {} pages",
+            entity_set,
+            rows.len(),
+            pages
+        );
+    }
+
+    Ok(rows)
+}
+
+pub async fn moduls(query: &ODataQuery) -> Result<Vec<Modul>, ApiError> {
+    fetch("beembk_moduls", query).await
+}
+
+pub async fn abschlusses(query: &ODataQuery) -> Result<Vec<Abschluss>, ApiError> {
+    fetch("beembk_abschlusses", query).await
+}
+
+pub async fn modulmappings(query: &ODataQuery) -> Result<Vec<ModulMapping>, ApiError> {
+    fetch("beembk_modulmappings", query).await
+}
+
+pub async fn handlungsziels(query: &ODataQuery) -> Result<Vec<Handlungsziel>, ApiError> {
+    fetch("beembk_handlungsziels", query).await
+}
+
+pub async fn handlungsnotwendigeskenntnises(
+    query: &ODataQuery,
+) -> Result<Vec<Handlungskenntnis>, ApiError> {
+    fetch("beembk_handlungsnotwendigeskenntnises", query).await
+}
+
+fn translated<'a>(
+    lang: Language,
+    de: &'a Option<String>,
+    fr: &'a Option<String>,
+    it: &'a Option<String>,
+) -> &'a str {
+    match lang {
+        Language::De => de,
+        Language::Fr => fr,
+        Language::It => it,
+    }
+    .as_deref()
+    .unwrap_or("")
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct Modul {
+    #[serde(rename = "beembk_modulnummer")]
+    pub number: Option<String>,
+    #[serde(rename = "beembk_modultitel")]
+    pub title_de: Option<String>,
+    #[serde(rename = "beembk_modultitel_fr")]
+    pub title_fr: Option<String>,
+    #[serde(rename = "beembk_modultitel_it")]
+    pub title_it: Option<String>,
+    #[serde(rename = "beembk_objektbeschreibung")]
+    pub description_de: Option<String>,
+    #[serde(rename = "beembk_objektbeschreibung_fr")]
+    pub description_fr: Option<String>,
+    #[serde(rename = "beembk_objektbeschreibung_it")]
+    pub description_it: Option<String>,
+    #[serde(rename = "beembk_kompetenz")]
+    pub competence_de: Option<String>,
+    #[serde(rename = "beembk_kompetenz_fr")]
+    pub competence_fr: Option<String>,
+    #[serde(rename = "beembk_kompetenz_it")]
+    pub competence_it: Option<String>,
+    #[serde(rename = "beembk_pdfname_de")]
+    pub pdf_de: Option<String>,
+    #[serde(rename = "beembk_pdfname_fr")]
+    pub pdf_fr: Option<String>,
+    #[serde(rename = "beembk_pdfname_it")]
+    pub pdf_it: Option<String>,
+    #[serde(rename = "beembk_version")]
+    pub version: Option<i64>,
+    #[serde(rename = "versionnumber")]
+    pub row_version: Option<i64>,
+    #[serde(rename = "modifiedon")]
+    pub modified_on: Option<String>,
+    #[serde(rename = "createdon")]
+    pub created_on: Option<String>,
+}
+
+impl Modul {
+    pub fn number(&self) -> Option<i64> {
+        self.number.as_deref()?.parse().ok()
+    }
+
+    pub fn title(&self, lang: Language) -> &str {
+        translated(lang, &self.title_de, &self.title_fr, &self.title_it)
+    }
+
+    pub fn description(&self, lang: Language) -> &str {
+        translated(
+            lang,
+            &self.description_de,
+            &self.description_fr,
+            &self.description_it,
+        )
+    }
+
+    pub fn competence(&self, lang: Language) -> &str {
+        translated(
+            lang,
+            &self.competence_de,
+            &self.competence_fr,
+            &self.competence_it,
+        )
+    }
+
+    pub fn pdf(&self, lang: Language) -> &str {
+        translated(lang, &self.pdf_de, &self.pdf_fr, &self.pdf_it)
+    }
+}
+
+/// A qualification (apprenticeship), exposed by the API as a job.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct Abschluss {
+    #[serde(rename = "beembk_abschlussid")]
+    pub id: Option<String>,
+    /// SBFI profession number, the job id used by the API.
+    #[serde(rename = "beembk_berufsnummer")]
+    pub number: Option<String>,
+    #[serde(rename = "beembk_abschlussname")]
+    pub name_de: Option<String>,
+    #[serde(rename = "beembk_abschlussname_fr")]
+    pub name_fr: Option<String>,
+    #[serde(rename = "beembk_abschlussname_it")]
+    pub name_it: Option<String>,
+    /// Length of the apprenticeship in years.
+    #[serde(rename = "beembk_ausbildungsdauer")]
+    pub duration: Option<i64>,
+    #[serde(rename = "modifiedon")]
+    pub modified_on: Option<String>,
+}
+
+impl Abschluss {
+    pub fn name(&self, lang: Language) -> &str {
+        translated(lang, &self.name_de, &self.name_fr, &self.name_it)
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct ModulMapping {
+    #[serde(rename = "versionnumber")]
+    pub row_version: Option<i64>,
+    #[serde(rename = "beembk_Modul")]
+    pub modul: Option<Modul>,
+    #[serde(rename = "beembk_Lernort")]
+    pub lernort: Option<Lernort>,
+    #[serde(rename = "beembk_Level")]
+    pub level: Option<Level>,
+    #[serde(rename = "beembk_Modultyp")]
+    pub modultyp: Option<Modultyp>,
+    #[serde(rename = "beembk_Abschluss")]
+    pub abschluss: Option<Abschluss>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct Lernort {
+    #[serde(rename = "beembk_lernortname")]
+    pub name_de: Option<String>,
+    #[serde(rename = "beembk_lernortname_fr")]
+    pub name_fr: Option<String>,
+    #[serde(rename = "beembk_lernortname_it")]
+    pub name_it: Option<String>,
+}
+
+impl Lernort {
+    pub fn name(&self, lang: Language) -> &str {
+        translated(lang, &self.name_de, &self.name_fr, &self.name_it)
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct Modultyp {
+    #[serde(rename = "beembk_modultypname")]
+    pub name_de: Option<String>,
+    #[serde(rename = "beembk_modultypname_fr")]
+    pub name_fr: Option<String>,
+    #[serde(rename = "beembk_modultypname_it")]
+    pub name_it: Option<String>,
+}
+
+impl Modultyp {
+    pub fn name(&self, lang: Language) -> &str {
+        translated(lang, &self.name_de, &self.name_fr, &self.name_it)
+    }
+
+    /// Wahlpflichtmodule are picked from a pool, only Pflichtmodule are mandatory.
+    pub fn is_mandatory(&self) -> bool {
+        self.name_de.as_deref() == Some("Pflichtmodul")
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct Level {
+    #[serde(rename = "beembk_levelname")]
+    pub name: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct Handlungsziel {
+    #[serde(rename = "beembk_handlungszielnr")]
+    pub number: Option<String>,
+    #[serde(rename = "beembk_handlungsziel")]
+    pub name_de: Option<String>,
+    #[serde(rename = "beembk_handlungsziel_fr")]
+    pub name_fr: Option<String>,
+    #[serde(rename = "beembk_handlungsziel_it")]
+    pub name_it: Option<String>,
+}
+
+impl Handlungsziel {
+    pub fn name(&self, lang: Language) -> &str {
+        translated(lang, &self.name_de, &self.name_fr, &self.name_it)
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct Handlungskenntnis {
+    #[serde(rename = "beembk_hanoknr")]
+    pub number: Option<String>,
+    #[serde(rename = "beembk_hanok")]
+    pub text_de: Option<String>,
+    #[serde(rename = "beembk_hanok_fr")]
+    pub text_fr: Option<String>,
+    #[serde(rename = "beembk_hanok_it")]
+    pub text_it: Option<String>,
+}
+
+impl Handlungskenntnis {
+    pub fn text(&self, lang: Language) -> &str {
+        translated(lang, &self.text_de, &self.text_fr, &self.text_it)
+    }
+}
@@ -0,0 +1,169 @@
+use crate::config;
+use crate::error::ApiError;
+use crate::job::get_jobs;
+use crate::model::Language;
+use crate::module::{get_module, get_modules};
+use std::collections::BTreeSet;
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use tokio::task::JoinSet;
+
+/// Apprenticeships last at most four years.
+const MAX_YEAR: i64 = 4;
+
+static RUNNING: AtomicBool = AtomicBool::new(false);
+
+/// Marks a warm-up as running until dropped, so runs never overlap.
+pub struct Running(());
+
+impl Running {
+    /// `None` while another warm-up is running.
+    pub fn claim() -> Option<Running> {
+        (!RUNNING.swap(true, Ordering::AcqRel)).then_some(Running(()))
+    }
+}
+
+impl Drop for Running {
+    fn drop(&mut self) {
+        RUNNING.store(false, Ordering::Release);
+    }
+}
+
+/// Runs `warm_cache` at startup and then every `CACHE_WARMUP_INTERVAL` seconds,
+/// skipping a run while one triggered through `/admin/cache/warmup` is in progress.
+pub async fn run_schedule() {
+    let interval = config::warmup_interval();
+
+    loop {
+        match Running::claim() {
+            Some(running) => warm_and_log(running).await,
+            None => println!("Cache warm-up skipped, another run is in progress"),
+        }
+
+        if interval.is_zero() {
+            return;
+        }
+        tokio::time::sleep(interval).await;
+    }
+}
+
+/// Runs `warm_cache` while holding `_running`, logging the outcome.
+pub async fn warm_and_log(_running: Running) {
+    let started = Instant::now();
+    match warm_cache().await {
+        Ok(warmed) => println!(
+            "Cache warm-up finished, {} lookups succeeded in {:.1}s",
+            warmed,
+            started.elapsed().as_secs_f64()
+        ),
+        Err(err) => eprintln!("Cache warm-up failed: {}", err),
+    }
+}
+
+/// Fills the cache with `/modules` for every job × language × year and with every
+/// module detail, running at most `CACHE_WARMUP_CONCURRENCY` requests at once.
+/// Returns the number of successful lookups, served from the cache or the upstream.
+pub async fn warm_cache() -> Result<usize, ApiError> {
+    let concurrency = config::warmup_concurrency();
+
+    let mut job_ids = vec![None];
+    for job in get_jobs(&None).await? {
+        if !job_ids.contains(&Some(job.id.clone())) {
+            job_ids.push(Some(job.id));
+        }
+    }
+    let mut years = vec![None];
+    years.extend((1..=MAX_YEAR).map(|year| Some(year.to_string())));
+
+    let mut lists = Vec::new();
+    for lang in Language::ALL {
+        for job_id in &job_ids {
+            for year in &years {
+                lists.push((lang, year.clone(), job_id.clone()));
+            }
+        }
+    }
+
+    let numbers = Arc::new(Mutex::new(BTreeSet::new()));
+    let found = numbers.clone();
+    // The job list itself was cached by `get_jobs`.
+    let mut warmed = 1;
+    warmed += run(
+        "module lists",
+        lists,
+        concurrency,
+        move |(lang, year, job_id)| {
+            let found = found.clone();
+            async move {
+                let modules =
+                    get_modules(&Some(lang.as_str().to_string()), &year, &job_id, &None).await?;
+                found
+                    .lock()
+                    .unwrap()
+                    .extend(modules.into_iter().map(|module| module.number));
+                Ok(())
+            }
+        },
+    )
+    .await;
+
+    let details: Vec<(Language, i64)> = numbers
+        .lock()
+        .unwrap()
+        .iter()
+        .flat_map(|number| Language::ALL.map(|lang| (lang, *number)))
+        .collect();
+    warmed += run(
+        "module details",
+        details,
+        concurrency,
+        |(lang, number)| async move {
+            get_module(&number.to_string(), &Some(lang.as_str().to_string()), &None)
+                .await
+                .map(|_| ())
+        },
+    )
+    .await;
+
+    Ok(warmed)
+}
+
+/// Runs `work` for every item with at most `concurrency` tasks in flight, logging
+/// progress and failures. Returns the number of successful items.
+async fn run<I, F, Fut>(label: &str, items: Vec<I>, concurrency: usize, work: F) -> usize
+where
+    I: Send + 'static,
+    F: Fn(I) -> Fut,
+    Fut: Future<Output = Result<(), ApiError>> + Send + 'static,
+{
+    let total = items.len();
+    let step = (total / 10).max(1);
+    let mut items = items.into_iter();
+    let mut tasks = JoinSet::new();
+    let (mut done, mut succeeded) = (0, 0);
+
+    loop {
+        while tasks.len() < concurrency.max(1) {
+            let Some(item) = items.next() else { break };
+            tasks.spawn(work(item));
+        }
+
+        let Some(result) = tasks.join_next().await else {
+            break;
+        };
+        done += 1;
+        match result {
+            Ok(Ok(())) => succeeded += 1,
+            Ok(Err(err)) => eprintln!("Cache warm-up of {} failed: {}", label, err),
+            Err(err) => eprintln!("Cache warm-up task for {} panicked: {}", label, err),
+        }
+
+        if done % step == 0 || done == total {
+            println!("Cache warm-up: {}/{} {}", done, total, label);
+        }
+    }
+
+    succeeded
+}
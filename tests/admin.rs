@@ -0,0 +1,123 @@
+mod common;
+
+use actix_web::http::StatusCode;
+use actix_web::test::TestRequest;
+use api_ict::cache;
+use api_ict::warmup::Running;
+use common::{get, send};
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+const ADMIN_TOKEN: &str = "admin-secret";
+
+static CACHE_DIR: OnceLock<PathBuf> = OnceLock::new();
+
+fn cache_dir() -> &'static PathBuf {
+    CACHE_DIR.get_or_init(|| {
+        common::fixture_server();
+
+        let cache_dir = env::temp_dir().join(format!("api-ict-admin-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&cache_dir);
+        fs::create_dir_all(&cache_dir).unwrap();
+        env::set_var("CACHE_DIR", &cache_dir);
+        env::set_var("DISABLE_CACHE", "false");
+        env::set_var("ADMIN_TOKEN", ADMIN_TOKEN);
+
+        cache_dir
+    })
+}
+
+fn authorized(req: TestRequest) -> TestRequest {
+    req.insert_header(("Authorization", format!("Bearer {}", ADMIN_TOKEN)))
+}
+
+fn keys(body: &serde_json::Value) -> Vec<String> {
+    body["entries"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|entry| entry["key"].as_str().unwrap().to_string())
+        .collect()
+}
+
+#[actix_web::test]
+async fn admin_routes_require_token() {
+    cache_dir();
+
+    let missing = send(TestRequest::get().uri("/admin/cache")).await;
+    assert_eq!(missing.status, StatusCode::UNAUTHORIZED);
+    assert_eq!(missing.body["code"], "unauthorized");
+
+    let wrong = send(
+        TestRequest::delete()
+            .uri("/admin/cache")
+            .insert_header(("Authorization", "Bearer wrong")),
+    )
+    .await;
+    assert_eq!(wrong.status, StatusCode::UNAUTHORIZED);
+}
+
+// Listing and purging share the cache directory, so they run as one sequence.
+#[actix_web::test]
+async fn lists_and_purges_cache_entries() {
+    cache_dir();
+
+    for uri in ["/modules/117", "/modules/110", "/modules?lang=fr"] {
+        assert_eq!(get(uri).await.status, StatusCode::OK, "{}", uri);
+    }
+
+    let listed = send(authorized(TestRequest::get().uri("/admin/cache"))).await;
+    assert_eq!(listed.status, StatusCode::OK);
+    let listed_keys = keys(&listed.body);
+    assert!(listed_keys.contains(&"module_117_de".to_string()));
+    assert!(listed_keys.contains(&"modules_fr_default_default".to_string()));
+    // The fixture server pages the module list two rows at a time.
+    assert!(listed.body["upstream"]["paged_responses"].as_u64().unwrap() > 0);
+    let entry = &listed.body["entries"][0];
+    assert!(entry["size"].as_u64().unwrap() > 0);
+    assert!(entry["age"].as_u64().is_some());
+
+    let purged = send(authorized(
+        TestRequest::delete().uri("/admin/cache?prefix=module_117_"),
+    ))
+    .await;
+    assert_eq!(purged.status, StatusCode::OK);
+    assert_eq!(purged.body["purged"], 1);
+
+    let purged = send(authorized(
+        TestRequest::delete().uri("/admin/cache/module_110_de"),
+    ))
+    .await;
+    assert_eq!(purged.body["purged"], 1);
+    let missing = send(authorized(
+        TestRequest::delete().uri("/admin/cache/module_110_de"),
+    ))
+    .await;
+    assert_eq!(missing.status, StatusCode::NOT_FOUND);
+
+    let remaining = keys(
+        &send(authorized(TestRequest::get().uri("/admin/cache")))
+            .await
+            .body,
+    );
+    assert!(!remaining.iter().any(|key| key.starts_with("module_")));
+
+    let purged = send(authorized(TestRequest::delete().uri("/admin/cache"))).await;
+    assert!(purged.body["purged"].as_u64().unwrap() >= 1);
+    assert!(cache::list_entries().await.unwrap().is_empty());
+}
+
+#[actix_web::test]
+async fn warmup_is_not_started_while_one_runs() {
+    cache_dir();
+
+    let running = Running::claim().unwrap();
+    let res = send(authorized(TestRequest::post().uri("/admin/cache/warmup"))).await;
+    assert_eq!(res.status, StatusCode::ACCEPTED);
+    assert_eq!(res.body["status"], "already_running");
+    drop(running);
+
+    assert!(Running::claim().is_some());
+}
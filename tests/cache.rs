@@ -0,0 +1,270 @@
+mod common;
+
+use actix_web::http::StatusCode;
+use api_ict::cache::{self, memory::MemoryCache};
+use common::{get, upstream_requests, UNAVAILABLE_MODULE};
+use serde_json::{json, Value};
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const DEFAULT_TTL: u64 = 20736000;
+
+static CACHE_DIR: OnceLock<PathBuf> = OnceLock::new();
+/// Held by tests that clear or purge the process-wide memory tier, or rely on it being kept.
+static MEMORY_TIER: tokio::sync::Mutex<()> = tokio::sync::Mutex::const_new(());
+
+fn cache_dir() -> &'static PathBuf {
+    CACHE_DIR.get_or_init(|| {
+        common::fixture_server();
+
+        let cache_dir = env::temp_dir().join(format!("api-ict-cache-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&cache_dir);
+        fs::create_dir_all(&cache_dir).unwrap();
+        env::set_var("CACHE_DIR", &cache_dir);
+        env::set_var("DISABLE_CACHE", "false");
+
+        cache_dir
+    })
+}
+
+/// The file backing `key` in the current format version.
+fn entry_path(key: &str) -> PathBuf {
+    cache_dir().join(format!("{}.json", cache::store_key(key)))
+}
+
+/// Writes a cache entry for `key` that was stored `age` seconds ago.
+fn write_entry(key: &str, data: Value, age: u64) -> PathBuf {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+    let path = entry_path(key);
+    fs::write(
+        &path,
+        json!({ "version": cache::CACHE_FORMAT_VERSION, "data": data, "timestamp": now - age })
+            .to_string(),
+    )
+    .unwrap();
+    path
+}
+
+fn module_detail(number: i64, name: &str) -> Value {
+    json!({
+        "number": number,
+        "description": "",
+        "name": name,
+        "year": 1,
+        "type": "",
+        "module_type": null,
+        "mandatory": false,
+        "version": 1,
+        "last_modified": "",
+        "creation_date": "",
+        "pdf": "",
+        "competence": "",
+        "objectives": [],
+        "usages": [],
+    })
+}
+
+#[test]
+fn memory_cache_evicts_least_recently_used() {
+    let mut memory = MemoryCache::new(2);
+
+    memory.insert("a", Arc::new(1_i64), 0);
+    memory.insert("b", Arc::new(2_i64), 0);
+    assert_eq!(memory.get::<i64>("a"), Some((Arc::new(1), 0)));
+
+    memory.insert("c", Arc::new(3_i64), 0);
+
+    assert_eq!(memory.len(), 2);
+    assert_eq!(memory.get::<i64>("a"), Some((Arc::new(1), 0)));
+    assert_eq!(memory.get::<i64>("b"), None);
+    assert_eq!(memory.get::<i64>("c"), Some((Arc::new(3), 0)));
+    assert_eq!(memory.get::<String>("c"), None);
+}
+
+#[actix_web::test]
+async fn dropped_flight_is_no_longer_in_flight() {
+    let flight = cache::flight::single_flight("dropped_flight", || async {
+        tokio::time::sleep(Duration::from_secs(60)).await;
+        Ok(1_i64)
+    });
+
+    assert!(tokio::time::timeout(Duration::from_millis(10), flight)
+        .await
+        .is_err());
+    assert!(!cache::flight::in_flight("dropped_flight"));
+}
+
+#[actix_web::test]
+async fn serves_repeated_requests_from_memory_then_disk() {
+    let _memory_tier = MEMORY_TIER.lock().await;
+    cache_dir();
+
+    let before = cache::stats();
+    let first = get("/modules?lang=it&year=1").await;
+    assert_eq!(first.status, StatusCode::OK);
+    assert!(entry_path("modules_it_1_default").exists());
+
+    let second = get("/modules?lang=it&year=1").await;
+    assert_eq!(second.body, first.body);
+
+    cache::clear_memory();
+    let third = get("/modules?lang=it&year=1").await;
+    assert_eq!(third.body, first.body);
+
+    let after = cache::stats();
+    assert!(after.memory_hits > before.memory_hits);
+    assert!(after.disk_hits > before.disk_hits);
+    assert!(after.disk_misses > before.disk_misses);
+}
+
+#[actix_web::test]
+async fn drops_memory_tier_after_purge_by_another_replica() {
+    let _memory_tier = MEMORY_TIER.lock().await;
+    cache_dir();
+
+    let first = get("/modules/117?lang=it").await;
+    assert_eq!(first.status, StatusCode::OK);
+
+    // Another replica purges and refills the shared store.
+    write_entry("module_117_it", module_detail(117, "Refilled elsewhere"), 0);
+    assert_eq!(get("/modules/117?lang=it").await.body, first.body);
+    cache::store::backend()
+        .unwrap()
+        .put("_purge_generation", b"other-replica")
+        .unwrap();
+
+    tokio::time::sleep(Duration::from_millis(1100)).await;
+    let res = get("/modules/117?lang=it").await;
+    assert_eq!(res.body["name"], "Refilled elsewhere");
+}
+
+#[actix_web::test]
+async fn serves_expired_entry_while_revalidating() {
+    let path = write_entry(
+        "module_117_de",
+        module_detail(117, "Stale name"),
+        DEFAULT_TTL + 100,
+    );
+
+    let res = get("/modules/117").await;
+    assert_eq!(res.status, StatusCode::OK);
+    assert_eq!(res.body["name"], "Stale name");
+
+    // Generous, the refresh competes with the other tests for the fake upstream.
+    let mut refreshed = Value::Null;
+    for _ in 0..200 {
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        refreshed = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        if refreshed["data"]["name"] != "Stale name" {
+            break;
+        }
+    }
+    assert_eq!(
+        refreshed["data"]["name"],
+        "Informatik- und Netzinfrastruktur für ein kleines Unternehmen realisieren"
+    );
+}
+
+#[actix_web::test]
+async fn serves_stale_entry_when_upstream_fails() {
+    let key = format!("module_{}_de", UNAVAILABLE_MODULE);
+    write_entry(
+        &key,
+        module_detail(999, "Last good data"),
+        DEFAULT_TTL + 2 * 86400,
+    );
+
+    let res = get(&format!("/modules/{}", UNAVAILABLE_MODULE)).await;
+    assert_eq!(res.status, StatusCode::OK);
+    assert_eq!(res.body["name"], "Last good data");
+
+    let res = get(&format!("/modules/{}?lang=fr", UNAVAILABLE_MODULE)).await;
+    assert_eq!(res.status, StatusCode::SERVICE_UNAVAILABLE);
+}
+
+#[actix_web::test]
+async fn refetches_and_quarantines_corrupt_entry() {
+    let path = entry_path("module_110_fr");
+    fs::write(&path, r#"{"version":1,"data":{"number":110,"na"#).unwrap();
+
+    let res = get("/modules/110?lang=fr").await;
+    assert_eq!(res.status, StatusCode::OK);
+    assert_eq!(res.body["number"], 110);
+
+    let quarantined = cache_dir()
+        .join("quarantine")
+        .join(path.file_name().unwrap());
+    assert!(fs::read_to_string(quarantined).unwrap().ends_with(r#""na"#));
+    let rewritten: Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+    assert_eq!(rewritten["version"], cache::CACHE_FORMAT_VERSION);
+}
+
+#[actix_web::test]
+async fn ignores_entries_from_other_format_versions() {
+    let _memory_tier = MEMORY_TIER.lock().await;
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+    // Written by an older release sharing the store, before and after namespacing.
+    let unversioned = cache_dir().join("module_162_fr.json");
+    let older = cache_dir().join("v1_module_162_fr.json");
+    for path in [&unversioned, &older] {
+        fs::write(
+            path,
+            json!({ "version": 1, "data": module_detail(162, "Old name"), "timestamp": now })
+                .to_string(),
+        )
+        .unwrap();
+    }
+
+    let res = get("/modules/162?lang=fr").await;
+    assert_eq!(res.status, StatusCode::OK);
+    assert_ne!(res.body["name"], "Old name");
+    assert!(entry_path("module_162_fr").exists());
+    assert!(unversioned.exists());
+    assert!(older.exists());
+    assert!(!cache_dir()
+        .join("quarantine")
+        .join("v1_module_162_fr.json")
+        .exists());
+
+    // Purging drops the key in every version.
+    assert!(cache::purge_key("module_162_fr").await.unwrap());
+    assert!(!entry_path("module_162_fr").exists());
+    assert!(!unversioned.exists());
+    assert!(!older.exists());
+}
+
+#[actix_web::test]
+async fn concurrent_misses_share_one_upstream_fetch() {
+    cache_dir();
+
+    // No other test here fetches module 431, the count is still taken as a difference.
+    let before = upstream_requests("beembk_moduls", "'431'");
+    let uri = "/modules/431?lang=it";
+    let responses = tokio::join!(get(uri), get(uri), get(uri), get(uri), get(uri));
+
+    for res in [
+        responses.0,
+        responses.1,
+        responses.2,
+        responses.3,
+        responses.4,
+    ] {
+        assert_eq!(res.status, StatusCode::OK);
+        assert_eq!(res.body["number"], 431);
+    }
+    assert_eq!(upstream_requests("beembk_moduls", "'431'") - before, 1);
+    assert!(!cache_dir().read_dir().unwrap().any(|entry| entry
+        .unwrap()
+        .file_name()
+        .to_string_lossy()
+        .contains(".tmp.")));
+}
@@ -0,0 +1,259 @@
+//! In-process stand-in for the modulbaukasten auth endpoint and the Dynamics
+//! OData API, serving the recorded responses in `tests/fixtures`.
+
+#![allow(dead_code)]
+
+use actix_web::http::StatusCode;
+use actix_web::{test, web, App, HttpRequest, HttpResponse, HttpServer};
+use api_ict::route;
+use regex::Regex;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::net::TcpListener;
+use std::sync::{Mutex, OnceLock};
+use std::thread;
+
+pub const TOKEN: &str = "eyJhbGciOiJub25lIiwidHlwIjoiSldUIn0.eyJleHAiOjQxMDI0NDQ4MDB9.fixture";
+
+/// Module numbers for which the fake upstream simulates an outage or a rejected token.
+pub const UNAVAILABLE_MODULE: &str = "999";
+pub const UNAUTHORIZED_MODULE: &str = "998";
+/// Module number for which the fake upstream links to a foreign host as the next page.
+pub const FOREIGN_LINK_MODULE: &str = "997";
+
+const PAGE_SIZE: usize = 2;
+
+static FIXTURE_SERVER: OnceLock<String> = OnceLock::new();
+static REQUESTS: Mutex<Vec<(String, String)>> = Mutex::new(Vec::new());
+
+/// Number of requests the fake upstream received for `entity_set` with a `$filter` containing `filter`.
+/// The count covers every test of the binary, and tests run in parallel: compare it
+/// before and after the requests of a test, or filter on what only that test fetches.
+pub fn upstream_requests(entity_set: &str, filter: &str) -> usize {
+    REQUESTS
+        .lock()
+        .unwrap()
+        .iter()
+        .filter(|(set, query)| set == entity_set && query.contains(filter))
+        .count()
+}
+
+/// Starts the fixture server once per test binary and points the API at it.
+pub fn fixture_server() -> &'static str {
+    FIXTURE_SERVER.get_or_init(|| {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("Failed to bind fixture server");
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+
+        thread::spawn(move || {
+            actix_web::rt::System::new().block_on(async move {
+                HttpServer::new(|| {
+                    App::new()
+                        .route("/assets/auth.php", web::get().to(auth))
+                        .route("/api/data/v9.1/{entity_set}", web::get().to(entity_set))
+                })
+                .workers(1)
+                .listen(listener)
+                .expect("Failed to listen on fixture server")
+                .run()
+                .await
+                .expect("Fixture server crashed")
+            })
+        });
+
+        env::set_var(
+            "MODULBAUKASTEN_AUTH_URL",
+            format!("{}/assets/auth.php", base_url),
+        );
+        env::set_var("DYNAMICS_BASE_URL", format!("{}/api/data/v9.1", base_url));
+        env::set_var("PDF_BASE_URL", "https://pdf.test/Module");
+        env::set_var("ODATA_PAGE_SIZE", PAGE_SIZE.to_string());
+        env::set_var("DISABLE_CACHE", "true");
+
+        base_url
+    })
+}
+
+pub fn fixture(name: &str) -> Option<Value> {
+    let path = format!(
+        "{}/tests/fixtures/{}.json",
+        env!("CARGO_MANIFEST_DIR"),
+        name
+    );
+    let content = fs::read_to_string(path).ok()?;
+
+    serde_json::from_str(&content).ok()
+}
+
+async fn auth() -> HttpResponse {
+    HttpResponse::Ok()
+        .content_type("application/json")
+        .json(fixture("auth").expect("Missing auth fixture"))
+}
+
+async fn entity_set(
+    req: HttpRequest,
+    path: web::Path<String>,
+    query: web::Query<HashMap<String, String>>,
+) -> HttpResponse {
+    let entity_set = path.into_inner();
+    let authorization = req
+        .headers()
+        .get("Authorization")
+        .and_then(|value| value.to_str().ok());
+
+    if authorization != Some(&format!("Bearer {}", TOKEN)) {
+        return HttpResponse::Unauthorized().finish();
+    }
+
+    let filter = query.get("$filter").cloned().unwrap_or_default();
+    REQUESTS
+        .lock()
+        .unwrap()
+        .push((entity_set.clone(), filter.clone()));
+
+    if filter.contains(&format!("'{}'", UNAVAILABLE_MODULE)) {
+        return HttpResponse::ServiceUnavailable().finish();
+    }
+    if filter.contains(&format!("'{}'", UNAUTHORIZED_MODULE)) {
+        return HttpResponse::Unauthorized().finish();
+    }
+
+    if filter.contains(&format!("'{}'", FOREIGN_LINK_MODULE)) {
+        return HttpResponse::Ok().json(json!({
+            "value": [],
+            "@odata.nextLink": format!("http://attacker.test/api/data/v9.1/{}", entity_set),
+        }));
+    }
+
+    let Some(fixture) = fixture(&entity_set) else {
+        return HttpResponse::NotFound().finish();
+    };
+
+    let mut rows = Vec::new();
+    for row in fixture["value"].as_array().cloned().unwrap_or_default() {
+        match matches_filter(&row, &filter) {
+            Some(true) => rows.push(row),
+            Some(false) => {}
+            None => {
+                return HttpResponse::BadRequest()
+                    .json(json!({ "error": format!("Unsupported filter: {}", filter) }))
+            }
+        }
+    }
+
+    let page_size = req
+        .headers()
+        .get("Prefer")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("odata.maxpagesize="))
+        .and_then(|value| value.parse::<usize>().ok())
+        .unwrap_or(usize::MAX);
+    let skip = query
+        .get("$skiptoken")
+        .and_then(|value| value.parse::<usize>().ok())
+        .unwrap_or(0);
+
+    let mut body = json!({
+        "@odata.context": fixture["@odata.context"],
+        "value": rows.iter().skip(skip).take(page_size).collect::<Vec<_>>(),
+    });
+
+    if skip.saturating_add(page_size) < rows.len() {
+        let query_string = req
+            .query_string()
+            .split('&')
+            .filter(|param| !param.starts_with("$skiptoken="))
+            .collect::<Vec<_>>()
+            .join("&");
+        body["@odata.nextLink"] = json!(format!(
+            "{}{}?{}&$skiptoken={}",
+            fixture_server(),
+            req.path(),
+            query_string,
+            skip + page_size
+        ));
+    }
+
+    HttpResponse::Ok().json(body)
+}
+
+/// Evaluates the subset of OData `$filter` syntax the API emits, `None` if unsupported.
+fn matches_filter(row: &Value, filter: &str) -> Option<bool> {
+    if filter.is_empty() {
+        return Some(true);
+    }
+
+    let function = Regex::new(r"^(startswith|contains)\(([\w/]+),'((?:[^']|'')*)'\)$").unwrap();
+    let equals = Regex::new(r"^([\w/]+) eq '((?:[^']|'')*)'$").unwrap();
+
+    let mut result = true;
+    for expression in filter.split(" and ") {
+        let expression = expression
+            .strip_prefix('(')
+            .and_then(|e| e.strip_suffix(')'))
+            .unwrap_or(expression);
+
+        let matched = if let Some(captures) = function.captures(expression) {
+            let value = property(row, &captures[2]);
+            let literal = captures[3].replace("''", "'");
+            match &captures[1] {
+                "startswith" => value.starts_with(&literal),
+                _ => value.contains(&literal),
+            }
+        } else if let Some(captures) = equals.captures(expression) {
+            property(row, &captures[1]) == captures[2].replace("''", "'")
+        } else {
+            return None;
+        };
+
+        result &= matched;
+    }
+
+    Some(result)
+}
+
+fn property(row: &Value, path: &str) -> String {
+    path.split('/')
+        .fold(row, |value, segment| &value[segment])
+        .as_str()
+        .unwrap_or("")
+        .to_string()
+}
+
+pub struct TestResponse {
+    pub status: StatusCode,
+    pub content_type: String,
+    pub headers: actix_web::http::header::HeaderMap,
+    pub body: Value,
+}
+
+/// Sends a GET request through `route::config` with the upstream pointed at the fixtures.
+pub async fn get(uri: &str) -> TestResponse {
+    send(test::TestRequest::get().uri(uri)).await
+}
+
+/// Sends `req` through `route::config` with the upstream pointed at the fixtures.
+pub async fn send(req: test::TestRequest) -> TestResponse {
+    fixture_server();
+
+    let app = test::init_service(App::new().configure(route::config)).await;
+    let res = test::call_service(&app, req.to_request()).await;
+
+    let status = res.status();
+    let headers = res.headers().clone();
+    let content_type = headers
+        .get("Content-Type")
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or("")
+        .to_string();
+    let body = test::read_body(res).await;
+
+    TestResponse {
+        status,
+        content_type,
+        headers,
+        body: serde_json::from_slice(&body).unwrap_or(Value::Null),
+    }
+}
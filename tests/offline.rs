@@ -0,0 +1,38 @@
+mod common;
+
+use actix_web::http::StatusCode;
+use common::get;
+use std::env;
+
+/// Points the API at a closed port, as if the upstream were down.
+fn without_upstream() {
+    common::fixture_server();
+    env::set_var(
+        "MODULBAUKASTEN_AUTH_URL",
+        "http://127.0.0.1:9/assets/auth.php",
+    );
+    env::set_var("DYNAMICS_BASE_URL", "http://127.0.0.1:9/api/data/v9.1");
+}
+
+#[actix_web::test]
+async fn jobs_fall_back_to_built_in_list() {
+    without_upstream();
+
+    let res = get("/jobs").await;
+
+    assert_eq!(res.status, StatusCode::OK);
+    let jobs = res.body.as_array().unwrap();
+    assert_eq!(jobs.len(), 12);
+    let mut ids: Vec<&str> = jobs.iter().map(|job| job["id"].as_str().unwrap()).collect();
+    ids.sort();
+    ids.dedup();
+    assert_eq!(ids.len(), 12, "job ids must be unique");
+    assert!(
+        jobs.iter()
+            .any(|job| job["id"] == "88602"
+                && job["name"] == "Informatiker/in EFZ Betriebsinformatik")
+    );
+    assert!(jobs
+        .iter()
+        .any(|job| job["id"] == "88614" && job["name"] == "Betriebsinformatiker/in EFZ (ab 2021)"));
+}
@@ -0,0 +1,46 @@
+mod common;
+
+use actix_web::http::StatusCode;
+use api_ict::recording::normalize_url;
+use common::get;
+use std::env;
+use std::fs;
+
+#[test]
+fn normalized_urls_ignore_host_and_parameter_order() {
+    assert_eq!(
+        normalize_url("https://ictbb.crm17.dynamics.com/api/data/v9.1/beembk_moduls?$top=1&$filter=beembk_modulnummer%20eq%20%27117%27"),
+        normalize_url("http://127.0.0.1:8080/api/data/v9.1/beembk_moduls?$filter=beembk_modulnummer eq '117'&$top=1"),
+    );
+}
+
+// Record and replay share process-wide settings, so they run as one sequence.
+#[actix_web::test]
+async fn replays_recorded_responses_without_network() {
+    common::fixture_server();
+
+    let record_dir = env::temp_dir().join(format!("api-ict-recordings-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&record_dir);
+    env::set_var("UPSTREAM_RECORD_DIR", &record_dir);
+
+    env::set_var("UPSTREAM_MODE", "record");
+    let recorded = get("/modules/117?lang=fr").await;
+    assert_eq!(recorded.status, StatusCode::OK);
+    assert!(fs::read_dir(&record_dir).unwrap().count() > 0);
+
+    env::set_var("UPSTREAM_MODE", "replay");
+    env::set_var(
+        "MODULBAUKASTEN_AUTH_URL",
+        "http://127.0.0.1:9/assets/auth.php",
+    );
+    env::set_var("DYNAMICS_BASE_URL", "http://127.0.0.1:9/api/data/v9.1");
+
+    let replayed = get("/modules/117?lang=fr").await;
+    assert_eq!(replayed.status, StatusCode::OK);
+    assert_eq!(replayed.body, recorded.body);
+
+    let missing = get("/modules/431?lang=fr").await;
+    assert_eq!(missing.status, StatusCode::SERVICE_UNAVAILABLE);
+
+    fs::remove_dir_all(&record_dir).unwrap();
+}
@@ -0,0 +1,634 @@
+mod common;
+
+use actix_web::http::StatusCode;
+use common::{
+    get, upstream_requests, FOREIGN_LINK_MODULE, UNAUTHORIZED_MODULE, UNAVAILABLE_MODULE,
+};
+use serde_json::{json, Value};
+
+const APPLICATION_DEVELOPMENT: &str = "88601";
+const SYSTEM_ENGINEERING: &str = "88603";
+
+/// Module number, year and jobs of the newest mapping of each module in the fixtures.
+const MODULES: [(i64, i64, &[&str]); 4] = [
+    (117, 1, &[APPLICATION_DEVELOPMENT, SYSTEM_ENGINEERING]),
+    (431, 1, &[APPLICATION_DEVELOPMENT]),
+    (110, 2, &[SYSTEM_ENGINEERING]),
+    (162, 3, &[APPLICATION_DEVELOPMENT]),
+];
+
+fn numbers(body: &Value) -> Vec<i64> {
+    let mut numbers: Vec<i64> = body
+        .as_array()
+        .expect("Expected a JSON array")
+        .iter()
+        .map(|module| module["number"].as_i64().unwrap())
+        .collect();
+    numbers.sort();
+    numbers
+}
+
+fn assert_problem(res: &common::TestResponse, status: StatusCode, code: &str) {
+    assert_eq!(res.status, status, "{}", res.body);
+    assert_eq!(res.content_type, "application/problem+json");
+    assert_eq!(res.body["status"], status.as_u16());
+    assert_eq!(res.body["code"], code);
+}
+
+#[actix_web::test]
+async fn index_lists_endpoints() {
+    let res = get("/").await;
+
+    assert_eq!(res.status, StatusCode::OK);
+    assert!(res.body["endpoints"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .any(|endpoint| endpoint["url"] == "/modules"));
+}
+
+#[actix_web::test]
+async fn jobs_are_translated() {
+    let res = get("/jobs?lang=fr").await;
+
+    assert_eq!(res.status, StatusCode::OK);
+    let job = res
+        .body
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|job| job["id"] == APPLICATION_DEVELOPMENT)
+        .unwrap();
+    assert_eq!(
+        job["name"],
+        "Informaticien/ne CFC développement d'applications"
+    );
+}
+
+#[actix_web::test]
+async fn jobs_come_from_upstream() {
+    let res = get("/jobs").await;
+
+    assert_eq!(res.status, StatusCode::OK);
+    let ids: Vec<&str> = res
+        .body
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|job| job["id"].as_str().unwrap())
+        .collect();
+    assert_eq!(ids, [APPLICATION_DEVELOPMENT, SYSTEM_ENGINEERING, "88616"]);
+    // The fixture lists an older Abschluss with the same number first.
+    assert_eq!(
+        res.body[0]["name"],
+        "Informatiker/in EFZ Applikationsentwicklung"
+    );
+    assert_eq!(
+        res.body[2]["name"],
+        "ICT-Fachfrau/ICT-Fachmann EFZ (ab 2026)"
+    );
+}
+
+#[actix_web::test]
+async fn job_detail() {
+    let res = get(&format!("/jobs/{}?lang=fr", APPLICATION_DEVELOPMENT)).await;
+
+    assert_eq!(res.status, StatusCode::OK);
+    assert_eq!(res.body["id"], APPLICATION_DEVELOPMENT);
+    assert_eq!(
+        res.body["name"],
+        serde_json::json!({
+            "de": "Informatiker/in EFZ Applikationsentwicklung",
+            "fr": "Informaticien/ne CFC développement d'applications",
+            "it": "Informatico/a AFC Sviluppo di applicazioni",
+        })
+    );
+    assert_eq!(res.body["duration"], 4);
+    assert_eq!(
+        res.body["modules"],
+        serde_json::json!({
+            "total": 3,
+            "per_year": { "1": 2, "3": 1 },
+            "per_learning_place": { "École professionnelle": 3 },
+        })
+    );
+    assert_eq!(res.body["links"]["self"], "/jobs/88601");
+    assert_eq!(res.body["links"]["modules"], "/modules?job_id=88601");
+    assert_eq!(
+        res.body["links"]["modules_per_year"]["4"],
+        "/modules?job_id=88601&year=4"
+    );
+}
+
+#[actix_web::test]
+async fn job_detail_counts_learning_places() {
+    let res = get(&format!("/jobs/{}", SYSTEM_ENGINEERING)).await;
+
+    assert_eq!(res.status, StatusCode::OK);
+    assert_eq!(
+        res.body["modules"]["per_learning_place"],
+        serde_json::json!({ "Berufsfachschule": 1, "Überbetriebliche Kurse": 1 })
+    );
+}
+
+#[actix_web::test]
+async fn job_detail_unknown_job() {
+    let res = get("/jobs/12345").await;
+
+    assert_problem(&res, StatusCode::NOT_FOUND, "unknown_job");
+}
+
+#[actix_web::test]
+async fn job_curriculum_groups_by_year_and_place() {
+    let res = get(&format!("/jobs/{}/curriculum?lang=fr", SYSTEM_ENGINEERING)).await;
+
+    assert_eq!(res.status, StatusCode::OK);
+    assert_eq!(res.body["job_id"], SYSTEM_ENGINEERING);
+    assert_eq!(
+        res.body["name"],
+        "Informaticien/ne CFC Technique des systèmes"
+    );
+
+    let years = res.body["years"].as_array().unwrap();
+    assert_eq!(years.len(), 2);
+    assert_eq!(years[0]["year"], 1);
+    assert_eq!(
+        years[0]["learning_places"][0]["name"],
+        "École professionnelle"
+    );
+    let module = &years[0]["learning_places"][0]["modules"][0];
+    assert_eq!(module["number"], 117);
+    assert_eq!(module["version"], 4);
+    assert_eq!(
+        module["module_type"],
+        serde_json::json!({ "id": "pflichtmodul", "name": "Module obligatoire" })
+    );
+    assert_eq!(module["mandatory"], true);
+
+    assert_eq!(years[1]["year"], 2);
+    assert_eq!(
+        years[1]["learning_places"][0]["name"],
+        "Cours interentreprises"
+    );
+    let module = &years[1]["learning_places"][0]["modules"][0];
+    assert_eq!(module["number"], 110);
+    assert_eq!(module["module_type"]["name"], "Module à choix obligatoire");
+    assert_eq!(module["mandatory"], false);
+}
+
+#[actix_web::test]
+async fn job_curriculum_unknown_job() {
+    let res = get("/jobs/12345/curriculum").await;
+
+    assert_problem(&res, StatusCode::NOT_FOUND, "unknown_job");
+}
+
+#[actix_web::test]
+async fn jobs_in_all_languages() {
+    let res = get("/jobs?lang=all").await;
+
+    assert_eq!(res.status, StatusCode::OK);
+    let job = res
+        .body
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|job| job["id"] == APPLICATION_DEVELOPMENT)
+        .unwrap();
+    assert_eq!(
+        job["name"],
+        json!({
+            "de": "Informatiker/in EFZ Applikationsentwicklung",
+            "fr": "Informaticien/ne CFC développement d'applications",
+            "it": "Informatico/a AFC Sviluppo di applicazioni",
+        })
+    );
+}
+
+#[actix_web::test]
+async fn jobs_reject_unsupported_language() {
+    let res = get("/jobs?lang=en").await;
+
+    assert_problem(&res, StatusCode::BAD_REQUEST, "invalid_language");
+}
+
+#[actix_web::test]
+async fn modules_for_every_filter_combination() {
+    let languages = [None, Some("de"), Some("fr"), Some("it"), Some("FR")];
+    let years = [None, Some(1), Some(2), Some(3), Some(4)];
+    let jobs = [
+        None,
+        Some(APPLICATION_DEVELOPMENT),
+        Some(SYSTEM_ENGINEERING),
+    ];
+
+    for lang in languages {
+        for year in years {
+            for job_id in jobs {
+                let mut params = Vec::new();
+                if let Some(lang) = lang {
+                    params.push(format!("lang={}", lang));
+                }
+                if let Some(year) = year {
+                    params.push(format!("year={}", year));
+                }
+                if let Some(job_id) = job_id {
+                    params.push(format!("job_id={}", job_id));
+                }
+                let uri = format!("/modules?{}", params.join("&"));
+
+                let res = get(&uri).await;
+                assert_eq!(res.status, StatusCode::OK, "{}", uri);
+
+                let mut expected: Vec<i64> = MODULES
+                    .iter()
+                    .filter(|(_, module_year, _)| year.is_none_or(|year| year == *module_year))
+                    .filter(|(_, _, module_jobs)| {
+                        job_id.is_none_or(|job_id| module_jobs.contains(&job_id))
+                    })
+                    .map(|(number, _, _)| *number)
+                    .collect();
+                expected.sort();
+                assert_eq!(numbers(&res.body), expected, "{}", uri);
+
+                for module in res.body.as_array().unwrap() {
+                    assert!(!module["name"].as_str().unwrap().is_empty(), "{}", uri);
+                    if let Some(year) = year {
+                        assert_eq!(module["year"], year, "{}", uri);
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[actix_web::test]
+async fn modules_use_newest_version_and_language() {
+    let res = get("/modules?lang=fr").await;
+
+    assert_eq!(res.status, StatusCode::OK);
+    let module = res
+        .body
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|module| module["number"] == 117)
+        .unwrap();
+    assert_eq!(module["version"], 4);
+    assert_eq!(module["year"], 1);
+    assert_eq!(
+        module["name"],
+        "Réaliser l'infrastructure informatique et réseau d'une petite entreprise"
+    );
+    assert_eq!(module["type"], "École professionnelle");
+    assert_eq!(module["creation_date"], "2021-09-28T05:33:38Z");
+}
+
+#[actix_web::test]
+async fn modules_expose_module_type() {
+    let res = get("/modules?lang=it").await;
+
+    assert_eq!(res.status, StatusCode::OK);
+    for module in res.body.as_array().unwrap() {
+        let elective = module["number"] == 110;
+        assert_eq!(module["mandatory"], !elective, "{}", module["number"]);
+        let expected = if elective {
+            serde_json::json!({ "id": "wahlpflichtmodul", "name": "Modulo a scelta obbligatoria" })
+        } else {
+            serde_json::json!({ "id": "pflichtmodul", "name": "Modulo obbligatorio" })
+        };
+        assert_eq!(module["module_type"], expected, "{}", module["number"]);
+    }
+}
+
+#[actix_web::test]
+async fn modules_filter_by_module_type() {
+    let res = get("/modules?module_type=Wahlpflichtmodul").await;
+    assert_eq!(res.status, StatusCode::OK);
+    assert_eq!(numbers(&res.body), [110]);
+
+    let res = get(&format!(
+        "/modules?module_type=pflichtmodul&job_id={}",
+        SYSTEM_ENGINEERING
+    ))
+    .await;
+    assert_eq!(numbers(&res.body), [117]);
+
+    let res = get("/modules?module_type=unknown").await;
+    assert_eq!(numbers(&res.body), Vec::<i64>::new());
+}
+
+#[actix_web::test]
+async fn modules_reject_invalid_year() {
+    let res = get("/modules?year=first").await;
+
+    assert_problem(&res, StatusCode::BAD_REQUEST, "invalid_year");
+}
+
+#[actix_web::test]
+async fn modules_reject_unknown_job() {
+    let res = get("/modules?job_id=12345").await;
+
+    assert_problem(&res, StatusCode::NOT_FOUND, "unknown_job");
+}
+
+#[actix_web::test]
+async fn modules_trailing_slash_redirects() {
+    let res = get("/modules/").await;
+
+    assert_eq!(res.status, StatusCode::MOVED_PERMANENTLY);
+    assert_eq!(res.headers.get("Location").unwrap(), "/modules");
+}
+
+#[actix_web::test]
+async fn module_detail() {
+    let res = get("/modules/117").await;
+
+    assert_eq!(res.status, StatusCode::OK);
+    assert_eq!(res.body["number"], 117);
+    assert_eq!(res.body["version"], 4);
+    assert_eq!(res.body["year"], 1);
+    assert_eq!(res.body["type"], "berufsfachschule");
+    assert_eq!(
+        res.body["pdf"],
+        "https://pdf.test/Module/117_4_Informatik- und Netzinfrastruktur für ein kleines Unternehmen realisieren.pdf"
+    );
+
+    let objectives = res.body["objectives"].as_array().unwrap();
+    assert_eq!(objectives.len(), 2);
+    assert_eq!(objectives[0]["name"], "Plant die Netzwerkinfrastruktur.");
+    assert_eq!(objectives[0]["number"], "117.1");
+    assert_eq!(
+        objectives[0]["details"],
+        json!([
+            { "number": "117.1.1", "text": "Kennt die Komponenten eines lokalen Netzwerks." },
+            { "number": "117.1.2", "text": "Kennt die Verkabelungsarten." }
+        ])
+    );
+    assert_eq!(objectives[1]["details"].as_array().unwrap().len(), 1);
+}
+
+#[actix_web::test]
+async fn module_detail_in_italian() {
+    let res = get("/modules/110?lang=it").await;
+
+    assert_eq!(res.status, StatusCode::OK);
+    assert_eq!(
+        res.body["name"],
+        "Analizzare e rappresentare dati con strumenti"
+    );
+    assert_eq!(res.body["type"], "corsi interaziendali");
+    assert_eq!(
+        res.body["module_type"]["name"],
+        "Modulo a scelta obbligatoria"
+    );
+    assert_eq!(res.body["mandatory"], false);
+    assert_eq!(res.body["year"], 2);
+    assert_eq!(
+        res.body["objectives"][1]["name"],
+        "Definisce valori limite per gli avvisi."
+    );
+}
+
+#[actix_web::test]
+async fn module_objectives_sorted_by_number() {
+    let res = get("/modules/110").await;
+
+    assert_eq!(res.status, StatusCode::OK);
+    let objectives = res.body["objectives"].as_array().unwrap();
+    let numbers: Vec<&str> = objectives
+        .iter()
+        .map(|objective| objective["number"].as_str().unwrap())
+        .collect();
+    assert_eq!(numbers, ["110.1", "110.2", "110.10"]);
+    assert_eq!(
+        objectives[2]["details"],
+        json!([{ "number": "110.10.1", "text": "Kennt Aufbewahrungsfristen." }])
+    );
+    // Other tests fetch module 110 in other languages concurrently.
+    assert!(upstream_requests("beembk_handlungsziels", "'110.'") >= 1);
+    assert_eq!(upstream_requests("beembk_handlungsziels", "'110')"), 0);
+}
+
+#[actix_web::test]
+async fn module_detail_lists_usages_of_newest_version() {
+    let res = get("/modules/117").await;
+
+    assert_eq!(res.status, StatusCode::OK);
+    assert_eq!(
+        res.body["usages"],
+        json!([
+            {
+                "job": { "id": "88601", "name": "Informatiker/in EFZ Applikationsentwicklung" },
+                "year": 1,
+                "type": "berufsfachschule",
+                "module_type": { "id": "pflichtmodul", "name": "Pflichtmodul" },
+                "mandatory": true,
+            },
+            {
+                "job": { "id": "88603", "name": "Informatiker/in EFZ Systemtechnik" },
+                "year": 1,
+                "type": "berufsfachschule",
+                "module_type": { "id": "pflichtmodul", "name": "Pflichtmodul" },
+                "mandatory": true,
+            },
+        ])
+    );
+}
+
+#[actix_web::test]
+async fn module_detail_for_job() {
+    let res = get("/modules/110?job_id=88603&lang=fr").await;
+
+    assert_eq!(res.status, StatusCode::OK);
+    assert_eq!(res.body["year"], 2);
+    assert_eq!(res.body["usages"][0]["job"]["id"], "88603");
+    assert_eq!(res.body["usages"][0]["type"], res.body["type"]);
+}
+
+#[actix_web::test]
+async fn module_detail_for_job_without_module() {
+    let res = get("/modules/110?job_id=88601").await;
+
+    assert_problem(&res, StatusCode::NOT_FOUND, "not_found");
+}
+
+#[actix_web::test]
+async fn module_detail_rejects_unknown_job() {
+    let res = get("/modules/110?job_id=12345").await;
+
+    assert_problem(&res, StatusCode::NOT_FOUND, "unknown_job");
+}
+
+#[actix_web::test]
+async fn module_objectives_endpoint() {
+    let res = get("/modules/117/objectives?lang=fr").await;
+
+    assert_eq!(res.status, StatusCode::OK);
+    let objectives = res.body.as_array().unwrap();
+    assert_eq!(objectives.len(), 2);
+    assert_eq!(objectives[0]["number"], "117.1");
+    assert_eq!(objectives[0]["name"], "Planifie l'infrastructure réseau.");
+    assert_eq!(
+        objectives[0]["details"][0],
+        json!({ "number": "117.1.1", "text": "Connaît les composants d'un réseau local." })
+    );
+}
+
+#[actix_web::test]
+async fn module_objectives_exclude_longer_module_numbers() {
+    let res = get("/modules/11/objectives").await;
+
+    assert_eq!(res.status, StatusCode::OK);
+    assert_eq!(
+        res.body,
+        json!([{
+            "number": "11.1",
+            "name": "Ziel eines anderen Moduls.",
+            "details": [{ "number": "11.1.1", "text": "Kenntnis eines anderen Moduls." }],
+        }])
+    );
+}
+
+#[actix_web::test]
+async fn module_objective_by_number() {
+    let res = get("/modules/110/objectives/10?lang=it").await;
+
+    assert_eq!(res.status, StatusCode::OK);
+    assert_eq!(res.body["number"], "110.10");
+    assert_eq!(res.body["name"], "Archivia i report.");
+    assert_eq!(res.body["details"][0]["number"], "110.10.1");
+}
+
+#[actix_web::test]
+async fn module_objective_not_found() {
+    let res = get("/modules/110/objectives/3").await;
+
+    assert_problem(&res, StatusCode::NOT_FOUND, "not_found");
+}
+
+#[actix_web::test]
+async fn module_objectives_of_unknown_module_not_found() {
+    let res = get("/modules/555/objectives").await;
+    assert_problem(&res, StatusCode::NOT_FOUND, "not_found");
+
+    let res = get("/modules/555/objectives/1").await;
+    assert_problem(&res, StatusCode::NOT_FOUND, "not_found");
+}
+
+#[actix_web::test]
+async fn module_objective_rejects_invalid_number() {
+    for uri in ["/modules/110/objectives/abc", "/modules/110/objectives/1.1"] {
+        let res = get(uri).await;
+        assert_problem(&res, StatusCode::BAD_REQUEST, "invalid_objective_number");
+    }
+}
+
+#[actix_web::test]
+async fn module_objectives_reject_invalid_language() {
+    let res = get("/modules/110/objectives?lang=en").await;
+
+    assert_problem(&res, StatusCode::BAD_REQUEST, "invalid_language");
+}
+
+#[actix_web::test]
+async fn modules_in_selected_languages() {
+    let res = get("/modules?lang=fr,de&job_id=88601&year=1").await;
+
+    assert_eq!(res.status, StatusCode::OK);
+    let module = res
+        .body
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|module| module["number"] == 117)
+        .unwrap();
+    assert_eq!(
+        module["name"],
+        json!({
+            "de": "Informatik- und Netzinfrastruktur für ein kleines Unternehmen realisieren",
+            "fr": "Réaliser l'infrastructure informatique et réseau d'une petite entreprise",
+        })
+    );
+    assert_eq!(module["module_type"]["id"], "pflichtmodul");
+    assert_eq!(module["module_type"]["name"]["fr"], "Module obligatoire");
+}
+
+#[actix_web::test]
+async fn module_detail_in_all_languages_from_one_fetch() {
+    let before = upstream_requests("beembk_moduls", "'431'");
+    let res = get("/modules/431?lang=all").await;
+
+    assert_eq!(res.status, StatusCode::OK);
+    assert_eq!(
+        res.body["name"],
+        json!({
+            "de": "Aufträge im eigenen Berufsumfeld selbstständig durchführen",
+            "fr": "Exécuter des mandats de manière autonome dans son propre environnement professionnel",
+            "it": "Eseguire autonomamente incarichi nel proprio contesto professionale",
+        })
+    );
+    assert_eq!(res.body["number"], 431);
+    assert!(res.body["pdf"]["it"]
+        .as_str()
+        .unwrap()
+        .starts_with("https://pdf.test/Module/431_"));
+    assert_eq!(upstream_requests("beembk_moduls", "'431'") - before, 1);
+}
+
+#[actix_web::test]
+async fn module_detail_rejects_unsupported_language_in_list() {
+    let res = get("/modules/110?lang=de,en").await;
+
+    assert_problem(&res, StatusCode::BAD_REQUEST, "invalid_language");
+}
+
+#[actix_web::test]
+async fn module_not_found() {
+    let res = get("/modules/555").await;
+
+    assert_problem(&res, StatusCode::NOT_FOUND, "not_found");
+}
+
+#[actix_web::test]
+async fn module_number_must_match_exactly() {
+    let res = get("/modules/1").await;
+
+    assert_problem(&res, StatusCode::NOT_FOUND, "not_found");
+}
+
+#[actix_web::test]
+async fn module_rejects_malformed_id() {
+    let res = get("/modules/1')%20or%20(1%20eq%201").await;
+
+    assert_problem(&res, StatusCode::BAD_REQUEST, "invalid_module_id");
+}
+
+#[actix_web::test]
+async fn module_upstream_unavailable() {
+    let res = get(&format!("/modules/{}", UNAVAILABLE_MODULE)).await;
+
+    assert_problem(
+        &res,
+        StatusCode::SERVICE_UNAVAILABLE,
+        "upstream_unavailable",
+    );
+}
+
+#[actix_web::test]
+async fn module_upstream_rejects_token() {
+    let res = get(&format!("/modules/{}", UNAUTHORIZED_MODULE)).await;
+
+    assert_problem(&res, StatusCode::BAD_GATEWAY, "upstream_auth");
+}
+
+#[actix_web::test]
+async fn module_rejects_next_link_to_foreign_host() {
+    let res = get(&format!("/modules/{}", FOREIGN_LINK_MODULE)).await;
+
+    assert_problem(
+        &res,
+        StatusCode::SERVICE_UNAVAILABLE,
+        "upstream_unavailable",
+    );
+}
@@ -0,0 +1,227 @@
+mod common;
+
+use actix_web::http::StatusCode;
+use api_ict::cache::store::{self, CacheStore, FileStore, InMemoryStore, RedisStore, SqliteStore};
+use common::{get, upstream_requests};
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = env::temp_dir().join(format!("api-ict-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    dir
+}
+
+/// Behaviour every backend must share.
+fn assert_store_contract(store: &dyn CacheStore) {
+    assert_eq!(store.get("module_117_de").unwrap(), None);
+    assert!(store.keys().unwrap().is_empty());
+
+    store.put("module_117_de", b"{\"a\":1}").unwrap();
+    store.put("module_117_fr", b"{}").unwrap();
+    store
+        .put("modules_de_default_default", &[0, 159, 146, 150])
+        .unwrap();
+    store.put("module_117_de", b"{\"a\":2}").unwrap();
+
+    assert_eq!(
+        store.get("module_117_de").unwrap(),
+        Some(b"{\"a\":2}".to_vec())
+    );
+    assert_eq!(
+        store.get("modules_de_default_default").unwrap(),
+        Some(vec![0, 159, 146, 150])
+    );
+    assert_eq!(
+        store.keys().unwrap(),
+        [
+            "module_117_de",
+            "module_117_fr",
+            "modules_de_default_default"
+        ]
+    );
+
+    assert!(store.remove("module_117_fr").unwrap());
+    assert!(!store.remove("module_117_fr").unwrap());
+    assert_eq!(store.get("module_117_fr").unwrap(), None);
+    assert_eq!(store.keys().unwrap().len(), 2);
+
+    store.quarantine("modules_de_default_default").unwrap();
+    store.quarantine("modules_fr_default_default").unwrap();
+    assert_eq!(store.get("modules_de_default_default").unwrap(), None);
+    assert!(!store
+        .keys()
+        .unwrap()
+        .contains(&"modules_de_default_default".to_string()));
+}
+
+#[test]
+fn in_memory_store() {
+    assert_store_contract(&InMemoryStore::new());
+}
+
+#[test]
+fn file_store() {
+    let dir = temp_dir("file-store");
+    assert_store_contract(&FileStore::new(&dir));
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn sqlite_store() {
+    let dir = temp_dir("sqlite-store");
+    let path = dir.join("cache.sqlite");
+    assert_store_contract(&SqliteStore::open(&path).unwrap());
+
+    // Entries survive reopening the database.
+    let reopened = SqliteStore::open(&path).unwrap();
+    assert_eq!(reopened.keys().unwrap(), ["module_117_de"]);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn redis_store() {
+    let address = fake_redis("secret");
+    let url = format!("redis://:secret@{}/2", address);
+
+    assert_store_contract(&RedisStore::new(&url).unwrap());
+
+    let wrong_password = RedisStore::new(&format!("redis://:wrong@{}", address)).unwrap();
+    assert!(wrong_password.get("module_117_de").is_err());
+    assert!(RedisStore::new("http://localhost").is_err());
+}
+
+// Both steps replace the process-wide backend, so they run as one sequence.
+#[actix_web::test]
+async fn responses_are_cached_in_the_configured_store() {
+    common::fixture_server();
+    env::set_var("DISABLE_CACHE", "false");
+
+    let backend = Arc::new(InMemoryStore::new());
+    store::set_backend(backend.clone());
+
+    let before = upstream_requests("beembk_moduls", "'162'");
+    for _ in 0..2 {
+        assert_eq!(get("/modules/162?lang=it").await.status, StatusCode::OK);
+    }
+    api_ict::cache::clear_memory();
+    assert_eq!(get("/modules/162?lang=it").await.status, StatusCode::OK);
+
+    assert_eq!(upstream_requests("beembk_moduls", "'162'") - before, 1);
+    assert!(backend
+        .get(&api_ict::cache::store_key("module_162_it"))
+        .unwrap()
+        .is_some());
+
+    // Nothing listens on port 1: every store call fails, requests are still answered.
+    store::set_backend(Arc::new(RedisStore::new("redis://127.0.0.1:1").unwrap()));
+    api_ict::cache::clear_memory();
+    for uri in ["/modules/162?lang=it", "/modules?lang=fr&year=2"] {
+        assert_eq!(get(uri).await.status, StatusCode::OK, "{}", uri);
+    }
+}
+
+/// Serves GET, SET, DEL, SCAN, AUTH and SELECT from a map, like a Redis server would.
+fn fake_redis(password: &'static str) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap().to_string();
+    let data = Arc::new(Mutex::new(BTreeMap::<Vec<u8>, Vec<u8>>::new()));
+
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            let data = data.clone();
+            thread::spawn(move || serve_redis(stream.unwrap(), password, data));
+        }
+    });
+
+    address
+}
+
+fn serve_redis(stream: TcpStream, password: &str, data: Arc<Mutex<BTreeMap<Vec<u8>, Vec<u8>>>>) {
+    let mut writer = stream.try_clone().unwrap();
+    let mut reader = BufReader::new(stream);
+    let mut authenticated = false;
+
+    while let Some(command) = read_command(&mut reader) {
+        let name = String::from_utf8_lossy(&command[0]).to_uppercase();
+        let mut data = data.lock().unwrap();
+
+        let reply = match name.as_str() {
+            "AUTH" if command.last().unwrap() == password.as_bytes() => {
+                authenticated = true;
+                b"+OK\r\n".to_vec()
+            }
+            "AUTH" => b"-WRONGPASS invalid password\r\n".to_vec(),
+            _ if !authenticated => b"-NOAUTH Authentication required.\r\n".to_vec(),
+            "SELECT" => b"+OK\r\n".to_vec(),
+            "SET" => {
+                data.insert(command[1].clone(), command[2].clone());
+                b"+OK\r\n".to_vec()
+            }
+            "GET" => match data.get(&command[1]) {
+                Some(value) => bulk(value),
+                None => b"$-1\r\n".to_vec(),
+            },
+            "DEL" => format!(":{}\r\n", data.remove(&command[1]).map_or(0, |_| 1)).into_bytes(),
+            "SCAN" => {
+                // Returns one key per call to exercise the cursor.
+                let cursor: usize = String::from_utf8_lossy(&command[1]).parse().unwrap();
+                let prefix = command[3].strip_suffix(b"*").unwrap();
+                let keys: Vec<&Vec<u8>> =
+                    data.keys().filter(|key| key.starts_with(prefix)).collect();
+                let next = if cursor + 1 < keys.len() {
+                    cursor + 1
+                } else {
+                    0
+                };
+
+                let mut reply = b"*2\r\n".to_vec();
+                reply.extend(bulk(next.to_string().as_bytes()));
+                match keys.get(cursor) {
+                    Some(key) => {
+                        reply.extend(b"*1\r\n");
+                        reply.extend(bulk(key));
+                    }
+                    None => reply.extend(b"*0\r\n"),
+                }
+                reply
+            }
+            _ => b"-ERR unknown command\r\n".to_vec(),
+        };
+
+        if writer.write_all(&reply).is_err() {
+            return;
+        }
+    }
+}
+
+fn bulk(value: &[u8]) -> Vec<u8> {
+    let mut reply = format!("${}\r\n", value.len()).into_bytes();
+    reply.extend(value);
+    reply.extend(b"\r\n");
+    reply
+}
+
+fn read_command(reader: &mut BufReader<TcpStream>) -> Option<Vec<Vec<u8>>> {
+    let mut line = String::new();
+    reader.read_line(&mut line).ok().filter(|read| *read > 0)?;
+    let count: usize = line.trim_end().strip_prefix('*')?.parse().ok()?;
+
+    (0..count)
+        .map(|_| {
+            let mut line = String::new();
+            reader.read_line(&mut line).ok()?;
+            let length: usize = line.trim_end().strip_prefix('$')?.parse().ok()?;
+            let mut arg = vec![0; length + 2];
+            reader.read_exact(&mut arg).ok()?;
+            arg.truncate(length);
+            Some(arg)
+        })
+        .collect()
+}
@@ -0,0 +1,48 @@
+mod common;
+
+use api_ict::cache;
+use api_ict::warmup::warm_cache;
+use std::env;
+use std::fs;
+
+#[actix_web::test]
+async fn warms_every_module_list_and_detail() {
+    common::fixture_server();
+
+    let cache_dir = env::temp_dir().join(format!("api-ict-warmup-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&cache_dir);
+    env::set_var("CACHE_DIR", &cache_dir);
+    env::set_var("DISABLE_CACHE", "false");
+    env::set_var("CACHE_WARMUP_CONCURRENCY", "3");
+
+    let warmed = warm_cache().await.unwrap();
+
+    // The job list, (all jobs + 3 jobs) × 3 languages × (all years + 4 years),
+    // then 4 modules × 3 languages.
+    assert_eq!(warmed, 1 + 4 * 3 * 5 + 4 * 3);
+
+    let keys: Vec<String> = cache::list_entries()
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|entry| entry.key)
+        .collect();
+    // Module details also cache their objectives.
+    assert_eq!(
+        keys.iter()
+            .filter(|key| !key.starts_with("objectives_"))
+            .count(),
+        warmed
+    );
+    for key in [
+        "jobs",
+        "modules_de_default_default",
+        "modules_it_2_88603",
+        "module_117_fr",
+        "module_162_it",
+    ] {
+        assert!(keys.contains(&key.to_string()), "{}", key);
+    }
+
+    fs::remove_dir_all(&cache_dir).unwrap();
+}